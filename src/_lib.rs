@@ -13,19 +13,38 @@ use cpp::*;
 mods! {
     /// Representations of C++ source code
     pub mod cpp {
+        inl mod condition;
         inl mod constant;
         inl mod enum_;
+        inl mod enumerator;
+        inl mod error_code;
+        inl mod expr;
         inl mod field;
         inl mod flag;
         inl mod function;
+        inl mod guid;
         inl mod ident;
         inl mod interface;
         inl mod issue;
         inl mod macro_;
         inl mod method;
+        inl mod method_wrapper;
         inl mod namespace;
+        inl mod pack;
+        inl mod static_assert;
         inl mod struct_;
         inl mod type_;
+        inl mod vtbl;
+    }
+
+    inl mod analysis {
+        inl mod constant_groups;
+        inl mod hierarchy;
+        inl mod layout;
+        inl mod method_patterns;
+        inl mod self_sized;
+        inl mod versions;
+        inl mod vtable;
     }
 
     inl mod ext {
@@ -0,0 +1,156 @@
+use crate::*;
+
+use std::collections::*;
+use std::fmt::{self, Debug, Formatter};
+
+
+
+/// A family of `#define` constants sharing a prefix and defined near each other, such as `WM_*`, `SW_*`, or `D3DCREATE_*`.
+///
+/// Many Win32 APIs only expose their enumerations this way, so these are synthesized by [`Root::constant_groups`].
+pub struct ConstantGroup {
+    /// The shared prefix of the group, including any trailing `_` (e.g. `D3DCREATE_`)
+    pub prefix:                 String,
+
+    pub kind:                   ConstantGroupKind,
+
+    /// Where the first member of this group was defined.
+    pub defined_at:             Location,
+
+    /// The constants in this group and their values, in source order.
+    pub members:                Vec<(Ident, i64)>,
+
+    pub(crate) _non_exhaustive: (),
+}
+
+/// Is a [`ConstantGroup`] more like an `enum` or a set of bit flags?
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ConstantGroupKind {
+    /// Sequential or otherwise arbitrary values (e.g. `SW_HIDE`, `SW_SHOWNORMAL`, ...)
+    Enum,
+
+    /// Single bits and combinations thereof (e.g. `D3DCREATE_FPU_PRESERVE`, `D3DCREATE_MULTITHREADED`, ...)
+    Flags,
+}
+
+impl ConstantGroup {
+    /// Guess if `values` are bit flags or not.
+    fn kind_of(values: impl Iterator<Item = i64>) -> ConstantGroupKind {
+        if looks_like_flags(values) { ConstantGroupKind::Flags } else { ConstantGroupKind::Enum }
+    }
+}
+
+impl Debug for ConstantGroup {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("ConstantGroup")
+            .field("prefix",        &self.prefix        )
+            .field("kind",          &self.kind          )
+            .field("members",       &self.members       )
+            .finish_non_exhaustive()
+    }
+}
+
+
+
+/// The most lines between constants that can still belong to the same group.
+const MAX_LINE_GAP : usize = 10;
+
+impl Root {
+    /// Group `#define` constants into pseudo-enums by shared prefix and source proximity.
+    ///
+    /// Only constants with values that [evaluate](Self::eval) are considered.
+    /// Prefixes shared by type names (e.g. `D3D12_` of `D3D12_RESOURCE_DESC`) are considered too generic to form a group.
+    pub fn constant_groups(&self) -> Vec<ConstantGroup> {
+        const MIN_MEMBERS   : usize = 2;
+
+        let type_names = self.interfaces.keys().chain(self.structs.keys()).chain(self.unions.keys()).chain(self.classes.keys()).chain(self.enums.keys());
+        let namespaces = type_names.flat_map(|id| id.match_indices('_').map(move |(i, _)| &id[..=i])).collect::<HashSet<&str>>();
+        let valid_prefix = |prefix: &str| prefix.len() >= 2 && prefix.ends_with('_') && !namespaces.contains(prefix);
+
+        let mut constants = self.constants.values_by_insert().filter_map(|c| {
+            let loc = c.defined_at.iter().next()?;
+            let value = self.eval(c.value.as_deref()?)?;
+            Some((loc, &c.id, value))
+        }).collect::<Vec<_>>();
+        constants.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut groups = Vec::new();
+        let mut group : Option<ConstantGroup> = None;
+        let mut prev_line = 0;
+        for (loc, id, value) in constants.into_iter() {
+            let line = loc.line_no_or_0();
+            if let Some(g) = group.as_mut() {
+                let prefix = common_prefix(&g.prefix, id);
+                let near = g.defined_at.path == loc.path && line.saturating_sub(prev_line) <= MAX_LINE_GAP;
+                if near && valid_prefix(prefix) && (g.members.len() < 2 || prefix == g.prefix) {
+                    g.prefix.truncate(prefix.len());
+                    g.members.push((id.clone(), value));
+                    prev_line = line;
+                    continue;
+                }
+            }
+
+            groups.extend(group.take().filter(|g| g.members.len() >= MIN_MEMBERS));
+            prev_line = line;
+            group = Some(ConstantGroup {
+                prefix:             id.rfind('_').map_or("", |i| &id[..=i]).into(),
+                kind:               ConstantGroupKind::Enum,
+                defined_at:         loc.clone(),
+                members:            vec![(id.clone(), value)],
+                _non_exhaustive:    (),
+            });
+        }
+        groups.extend(group.take().filter(|g| g.members.len() >= MIN_MEMBERS));
+
+        for g in groups.iter_mut() {
+            g.kind = ConstantGroup::kind_of(g.members.iter().map(|(_, v)| *v));
+        }
+        groups
+    }
+}
+
+/// The longest common prefix of `a` and `b` ending in `_` (or `""` if none)
+fn common_prefix<'a>(a: &'a str, b: &str) -> &'a str {
+    let common = a.bytes().zip(b.bytes()).take_while(|(a, b)| a == b).count();
+    a[..common].rfind('_').map_or("", |i| &a[..=i])
+}
+
+
+
+#[test] fn test_constant_groups() {
+    let root = Root::from_cpp_src(&[r#"
+        typedef struct D3D12_BOX { UINT left; } D3D12_BOX;
+
+        #define D3D12_16BIT_INDEX_STRIP_CUT_VALUE   ( 0xffff )
+        #define D3D12_APPEND_ALIGNED_ELEMENT        ( 0xffffffff )
+
+        #define SW_HIDE             0
+        #define SW_SHOWNORMAL       1
+        #define SW_NORMAL           1
+        #define SW_SHOWMINIMIZED    2
+
+        #define D3DCREATE_FPU_PRESERVE              0x00000002L
+        #define D3DCREATE_MULTITHREADED             0x00000004L
+
+        #define D3DCREATE_PUREDEVICE                0x00000010L
+        #define D3DCREATE_SOFTWARE_VERTEXPROCESSING 0x00000020L
+        #define D3DCREATE_ALL                       (D3DCREATE_FPU_PRESERVE | D3DCREATE_MULTITHREADED)
+
+        #define D3DCAPS_READ_SCANLINE   0x00020000L
+        #define D3DCAPS_OVERLAY         0x00000800L
+        #define D3DCAPS2_FULLSCREENGAMMA 0x00020000L
+    "#, &"\n".repeat(MAX_LINE_GAP + 1), r#"
+        #define WM_CREATE 0x0001
+        #define WM_DESTROY 0x0002
+    "#].concat());
+
+    let groups = root.constant_groups();
+    let summary = groups.iter().map(|g| (g.prefix.as_str(), g.kind, g.members.len())).collect::<Vec<_>>();
+    assert_eq!(summary, [
+        ("SW_",         ConstantGroupKind::Enum,    4),
+        ("D3DCREATE_",  ConstantGroupKind::Flags,   5),
+        ("D3DCAPS_",    ConstantGroupKind::Flags,   2),
+        ("WM_",         ConstantGroupKind::Enum,    2),
+    ]);
+    assert_eq!(groups[1].members[4], (Ident::from("D3DCREATE_ALL"), 6));
+}
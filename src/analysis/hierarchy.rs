@@ -0,0 +1,191 @@
+use crate::*;
+
+use std::collections::*;
+use std::fmt::{self, Debug, Formatter};
+
+
+
+/// A reverse index of interface inheritance, as returned by [`Root::interface_hierarchy`].
+///
+/// [`Interface::base`] only points upwards - this also allows walking from e.g. `ID3D12Object` down to `ID3D12Device`, `ID3D12Resource`, etc.
+pub struct InterfaceHierarchy<'a> {
+    root:       &'a Root,
+    derived:    HashMap<&'a str, Vec<&'a Ident>>,
+}
+
+/// A broken link in an interface hierarchy, as returned by [`InterfaceHierarchy::problems`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HierarchyProblem {
+    /// `interface` derives from `base`, which isn't a known interface.
+    MissingBase { interface: Ident, base: Ident },
+
+    /// The interfaces derive from each other in a loop (e.g. `[IA, IB]` for `IA : IB` and `IB : IA`.)
+    CyclicBases(Vec<Ident>),
+}
+
+impl Debug for InterfaceHierarchy<'_> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        let derived = self.derived.iter().collect::<BTreeMap<_, _>>();
+        fmt.debug_struct("InterfaceHierarchy")
+            .field("derived",       &derived            )
+            .finish_non_exhaustive()
+    }
+}
+
+
+
+impl Root {
+    /// Index which interfaces derive from which.
+    ///
+    /// The index borrows `self`, so build it once and reuse it for multiple queries.
+    pub fn interface_hierarchy(&self) -> InterfaceHierarchy<'_> {
+        let mut derived = HashMap::<&str, Vec<&Ident>>::new();
+        for interface in self.interfaces.values_by_insert() {
+            if let Some(base) = interface.base.as_ref() {
+                derived.entry(base.as_str()).or_default().push(&interface.id);
+            }
+        }
+        InterfaceHierarchy { root: self, derived }
+    }
+
+    /// The bases of interface `id`, nearest first (e.g. `[ID3D12Pageable, ID3D12DeviceChild, ID3D12Object, IUnknown]` for `ID3D12Resource`.)
+    ///
+    /// Ends with the first base that isn't a known interface, or just before repeating an interface if the bases are cyclic.
+    pub(crate) fn interface_bases(&self, id: &str) -> Vec<&Ident> {
+        let mut bases = Vec::<&Ident>::new();
+        let mut next = self.interfaces.get(id).and_then(|i| i.base.as_ref());
+        while let Some(base) = next {
+            if base.as_str() == id || bases.contains(&base) { break }
+            bases.push(base);
+            next = self.interfaces.get(base).and_then(|i| i.base.as_ref());
+        }
+        bases
+    }
+}
+
+impl<'a> InterfaceHierarchy<'a> {
+    /// Interfaces directly deriving from `id`, in definition order.
+    pub fn direct_derived(&self, id: &str) -> &[&'a Ident] {
+        self.derived.get(id).map_or(&[], |d| &d[..])
+    }
+
+    /// Interfaces directly or indirectly deriving from `id`, breadth first (e.g. `ID3D12Device` before `ID3D12Device1`.)
+    pub fn derived(&self, id: &str) -> Vec<&'a Ident> {
+        let mut seen    = BTreeSet::<&str>::new();
+        let mut derived = Vec::new();
+        let mut queue   = VecDeque::from([id]);
+        seen.insert(id);
+        while let Some(id) = queue.pop_front() {
+            for &d in self.direct_derived(id) {
+                if !seen.insert(d.as_str()) { continue } // cyclic bases
+                derived.push(d);
+                queue.push_back(d.as_str());
+            }
+        }
+        derived
+    }
+
+    /// The bases of `id`, nearest first (e.g. `[ID3D12Pageable, ID3D12DeviceChild, ID3D12Object, IUnknown]` for `ID3D12Resource`.)
+    ///
+    /// Ends early with a missing base, or just before repeating an interface if the bases are cyclic.
+    pub fn bases(&self, id: &str) -> Vec<&'a Ident> {
+        self.root.interface_bases(id)
+    }
+
+    /// How many bases `id` has (e.g. `0` for `IUnknown`, `1` for `IDXGIObject`.)
+    ///
+    /// Returns [`None`] if `id` or any of it's bases are unknown, or if the bases are cyclic.
+    pub fn depth(&self, id: &str) -> Option<usize> {
+        self.root.interfaces.get(id)?;
+        let bases = self.bases(id);
+        match bases.last() {
+            None        => Some(0),
+            Some(last)  => self.root.interfaces.get(last.as_str())?.base.is_none().then_some(bases.len()),
+        }
+    }
+
+    /// The nearest interface that both `a` and `b` are or derive from (e.g. `ID3D12DeviceChild` for `ID3D12Resource` and `ID3D12CommandList`.)
+    ///
+    /// This may be `a` or `b` themselves, if one derives from the other.
+    pub fn common_ancestor(&self, a: &str, b: &str) -> Option<&'a Ident> {
+        let a = self.self_and_bases(a)?;
+        let b = self.self_and_bases(b)?;
+        a.into_iter().find(|a| b.contains(a))
+    }
+
+    /// Find interfaces deriving from unknown bases, and interfaces deriving from each other in a loop, in definition order.
+    pub fn problems(&self) -> Vec<HierarchyProblem> {
+        let mut problems = Vec::new();
+        let mut cyclic = BTreeSet::<Ident>::new();
+        for interface in self.root.interfaces.values_by_insert() {
+            let base = if let Some(base) = interface.base.as_ref() { base } else { continue };
+            if self.root.interfaces.get(base).is_none() {
+                problems.push(HierarchyProblem::MissingBase { interface: interface.id.clone(), base: base.clone() });
+                continue;
+            }
+
+            if cyclic.contains(&interface.id) { continue }
+            let bases = self.bases(&interface.id);
+            let last = match bases.last() { Some(last) => last, None => continue };
+            let next = match self.root.interfaces.get(last.as_str()).and_then(|i| i.base.as_ref()) { Some(next) => next, None => continue };
+            if next != &interface.id { continue } // not cyclic, or leads into a cycle that doesn't include `interface`
+
+            let mut cycle = vec![interface.id.clone()];
+            cycle.extend(bases.into_iter().cloned());
+            cyclic.extend(cycle.iter().cloned());
+            problems.push(HierarchyProblem::CyclicBases(cycle));
+        }
+        problems
+    }
+
+    fn self_and_bases(&self, id: &str) -> Option<Vec<&'a Ident>> {
+        let interface = self.root.interfaces.get(id)?;
+        let mut chain = vec![&interface.id];
+        chain.extend(self.bases(id));
+        Some(chain)
+    }
+}
+
+
+
+#[test] fn test_interface_hierarchy() {
+    let root = Root::from_cpp_src(r#"
+        DECLARE_INTERFACE(IUnknown) {};
+        DECLARE_INTERFACE_(ID3D12Object,        IUnknown)           {};
+        DECLARE_INTERFACE_(ID3D12DeviceChild,   ID3D12Object)       {};
+        DECLARE_INTERFACE_(ID3D12Device,        ID3D12Object)       {};
+        DECLARE_INTERFACE_(ID3D12Pageable,      ID3D12DeviceChild)  {};
+        DECLARE_INTERFACE_(ID3D12Resource,      ID3D12Pageable)     {};
+        DECLARE_INTERFACE_(ID3D12CommandList,   ID3D12DeviceChild)  {};
+        DECLARE_INTERFACE_(ID3D12Device1,       ID3D12Device)       {};
+        DECLARE_INTERFACE_(IOrphan,             IMissing)           {};
+        DECLARE_INTERFACE_(ICycleA,             ICycleB)            {};
+        DECLARE_INTERFACE_(ICycleB,             ICycleA)            {};
+        DECLARE_INTERFACE_(IIntoCycle,          ICycleA)            {};
+    "#);
+    let h = root.interface_hierarchy();
+    let strs = |ids: &[&Ident]| ids.iter().map(|i| i.to_string()).collect::<Vec<_>>();
+
+    assert_eq!(strs(h.direct_derived("ID3D12Object")), ["ID3D12DeviceChild", "ID3D12Device"]);
+    assert_eq!(strs(&h.derived("ID3D12Object")), ["ID3D12DeviceChild", "ID3D12Device", "ID3D12Pageable", "ID3D12CommandList", "ID3D12Device1", "ID3D12Resource"]);
+    assert_eq!(strs(&h.derived("ICycleA")), ["ICycleB", "IIntoCycle"]);
+    assert_eq!(strs(&h.bases("ID3D12Resource")), ["ID3D12Pageable", "ID3D12DeviceChild", "ID3D12Object", "IUnknown"]);
+    assert_eq!(strs(&h.bases("IIntoCycle")), ["ICycleA", "ICycleB"]);
+
+    assert_eq!(h.depth("IUnknown"),         Some(0));
+    assert_eq!(h.depth("ID3D12Resource"),   Some(4));
+    assert_eq!(h.depth("IOrphan"),          None);
+    assert_eq!(h.depth("ICycleA"),          None);
+    assert_eq!(h.depth("IIntoCycle"),       None);
+    assert_eq!(h.depth("IMissing"),         None);
+
+    assert_eq!(h.common_ancestor("ID3D12Resource", "ID3D12CommandList").map(|i| i.as_str()),    Some("ID3D12DeviceChild"));
+    assert_eq!(h.common_ancestor("ID3D12Resource", "ID3D12Device1").map(|i| i.as_str()),        Some("ID3D12Object"));
+    assert_eq!(h.common_ancestor("ID3D12Resource", "ID3D12Object").map(|i| i.as_str()),         Some("ID3D12Object"));
+    assert_eq!(h.common_ancestor("ID3D12Resource", "IOrphan").map(|i| i.as_str()),              None);
+
+    assert_eq!(h.problems(), [
+        HierarchyProblem::MissingBase { interface: "IOrphan".into(), base: "IMissing".into() },
+        HierarchyProblem::CyclicBases(vec!["ICycleA".into(), "ICycleB".into()]),
+    ]);
+}
@@ -0,0 +1,532 @@
+use crate::*;
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+
+
+
+/// A target architecture, for the purposes of [`Layout`]s.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Arch {
+    X86,
+    X64,
+    ARM64,
+}
+
+/// The size, alignment, and field offsets of a `struct` or `union`, per MSVC's layout rules.
+///
+/// Fields are aligned to the lesser of their natural alignment and [`AggregateData::pack`], but never less than their `DECLSPEC_ALIGN`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Layout {
+    pub size:                   u64,
+    pub align:                  u64,
+
+    /// Fields in declaration order, including anonymous aggregates and bitfields.
+    /// Fields declared under preprocessor conditions (e.g. `#ifdef _WIN64`) are only included if the condition holds for the [`Arch`].
+    pub fields:                 Vec<FieldLayout>,
+
+    pub(crate) _non_exhaustive: (),
+}
+
+/// The offset, size, and alignment of a single [`Field`] within a [`Layout`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldLayout {
+    pub id:                     Ident,
+
+    /// Offset in bytes from the start of the containing aggregate (of the storage unit, for bitfields.)
+    pub offset:                 u64,
+
+    /// Size in bytes (of the entire array for arrays, of the storage unit for bitfields.)
+    pub size:                   u64,
+    pub align:                  u64,
+
+    /// See [`Field::bit_layout`]
+    pub bit_layout:             Option<BitfieldLayout>,
+
+    /// The layout of an inline `struct` / `union` field.
+    pub nested:                 Option<Layout>,
+
+    pub(crate) _non_exhaustive: (),
+}
+
+impl Arch {
+    pub const ALL : [Arch; 3] = [Arch::X86, Arch::X64, Arch::ARM64];
+
+    pub fn pointer_size(self) -> u64 {
+        match self {
+            Arch::X86                   => 4,
+            Arch::X64 | Arch::ARM64     => 8,
+        }
+    }
+
+    /// Architecture macros defined by MSVC or `windows.h` when targeting this architecture (e.g. `_WIN64`, `_M_X64`, `_AMD64_`.)
+    pub fn defines(self) -> &'static [&'static str] {
+        match self {
+            Arch::X86   => &["_WIN32", "_M_IX86", "_X86_"],
+            Arch::X64   => &["_WIN32", "_WIN64", "_M_X64", "_M_AMD64", "_AMD64_"],
+            Arch::ARM64 => &["_WIN32", "_WIN64", "_M_ARM64", "_ARM64_"],
+        }
+    }
+
+    /// Evaluate a preprocessor condition such as `defined(_WIN64) && !defined(_M_ARM64)` (e.g. a [`Field::condition`]) for this architecture.
+    ///
+    /// Returns [`None`] if the condition depends on anything other than architecture macros (e.g. `(DIRECT3D_VERSION >= 0x0900)`.)
+    pub fn eval_condition(self, condition: &str) -> Option<bool> {
+        self.eval_condition_in(condition, &NoMacros)
+    }
+
+    /// Evaluate a preprocessor condition for this architecture, expanding non-architecture macros via `ctx`.
+    ///
+    /// `defined(...)` of a non-architecture macro that `ctx` doesn't define is treated as unknown rather than false, as it may be defined elsewhere.
+    pub(crate) fn eval_condition_in(self, condition: &str, ctx: &dyn ExprContext) -> Option<bool> {
+        let arch = |id: &str| ARCH_MACROS.contains(&id).then(|| if self.defines().contains(&id) { "1" } else { "0" });
+        let mut expr = String::new();
+        let mut rest = condition;
+        while let Some(start) = rest.find(|ch: char| ch.is_ascii_word_character()) {
+            expr.push_str(&rest[..start]);
+            let end = rest[start..].find(|ch: char| !ch.is_ascii_word_character()).map_or(rest.len(), |e| start + e);
+            let word = &rest[start..end];
+            rest = &rest[end..];
+            if word.starts_with(|ch: char| ch.is_ascii_digit()) {
+                expr.push_str(word);
+            } else if word == "defined" {
+                // `defined(ID)` or `defined ID`
+                let args = rest.trim_start();
+                let (id, after) = match args.strip_prefix('(') {
+                    Some(args)  => { let (id, after) = args.split_once(')')?; (id.trim(), after) },
+                    None        => args.split_at(args.find(|ch: char| !ch.is_ascii_word_character()).unwrap_or(args.len())),
+                };
+                let defined = ctx.constant(id).is_some() || ctx.macro_(id).is_some();
+                expr.push_str(arch(id).or_else(|| defined.then_some("1"))?);
+                rest = after;
+            } else {
+                expr.push_str(arch(word).unwrap_or(word));
+            }
+        }
+        expr.push_str(rest);
+        Some(eval_expr(&expr, ctx)? != 0)
+    }
+}
+
+/// All macros recognized by [`Arch::eval_condition`].
+const ARCH_MACROS : &[&str] = &["_WIN32", "_WIN64", "_M_IX86", "_X86_", "_M_X64", "_M_AMD64", "_AMD64_", "_M_ARM64", "_ARM64_"];
+
+struct NoMacros;
+impl ExprContext for NoMacros {
+    fn constant(&self, _id: &str) -> Option<&str> { None }
+    fn macro_(&self, _id: &str) -> Option<(&[Ident], &str)> { None }
+}
+
+impl Display for Arch {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(fmt, "{}", match *self {
+            Arch::X86   => "x86",
+            Arch::X64   => "x64",
+            Arch::ARM64 => "arm64",
+        })
+    }
+}
+
+impl Layout {
+    /// Find a (non-bitfield) field by id, searching through anonymous aggregates, returning it's offset from the start of this layout.
+    pub fn offset_of(&self, id: &str) -> Option<u64> {
+        self.find_field(id).map(|(offset, _)| offset)
+    }
+
+    /// Find a field by id or `a.b` path, searching through anonymous aggregates, returning it's offset from the start of this layout.
+    pub fn find_field(&self, path: &str) -> Option<(u64, &FieldLayout)> {
+        let (id, rest) = match path.split_once('.') { Some((id, rest)) => (id.trim(), Some(rest.trim())), None => (path.trim(), None) };
+        self.fields.iter().find_map(|f| {
+            if f.id.as_str() == id {
+                return match rest {
+                    None        => Some((f.offset, f)),
+                    Some(rest)  => f.nested.as_ref()?.find_field(rest).map(|(offset, f2)| (f.offset + offset, f2)),
+                }
+            }
+            let nested = f.nested.as_ref().filter(|_| f.id.starts_with("_anonymous_"))?;
+            nested.find_field(path).map(|(offset, f2)| (f.offset + offset, f2))
+        })
+    }
+}
+
+const MAX_DEPTH : usize = 32;
+
+impl Root {
+    /// Compute the [`Layout`] of the `struct`, `union`, or `class` named `id` for `arch`.
+    pub fn layout(&self, id: &str, arch: Arch) -> Result<Layout, String> {
+        let agg = self.aggregate(id, 0).ok_or_else(|| format!("no `struct` or `union` named `{}`", id))?;
+        self.layout_aggregate(agg, arch, 0)
+    }
+
+    /// Compute the [`Layout`]s of all [`Root::structs`], [`Root::unions`], and [`Root::classes`] for `arch`.
+    pub fn layouts(&self, arch: Arch) -> BTreeMap<Ident, Result<Layout, String>> {
+        self.structs.values_by_key().chain(self.unions.values_by_key()).chain(self.classes.values_by_key())
+            .map(|agg| (agg.id.clone(), self.layout_aggregate(agg, arch, 0)))
+            .collect()
+    }
+
+    /// The size and alignment of a type such as `UINT`, `const WCHAR *`, or `D3D12_GPU_VIRTUAL_ADDRESS`.
+    pub fn size_align_of(&self, ty: &str, arch: Arch) -> Result<(u64, u64), String> {
+        self.size_align_of_impl(ty, arch, 0)
+    }
+
+    /// Evaluate an expression such as `sizeof(FOO) == 24`, replacing `sizeof(...)`, `FIELD_OFFSET(...)`, `TYPE_ALIGNMENT(...)`, etc. with their values for `arch`.
+    pub fn eval_layout_expr(&self, expr: &str, arch: Arch) -> Option<i64> {
+        let mut out = String::new();
+        let mut rest = expr;
+        while let Some(start) = rest.find(|ch: char| ch.is_ascii_word_character()) {
+            out.push_str(&rest[..start]);
+            let end = rest[start..].find(|ch: char| !ch.is_ascii_word_character()).map_or(rest.len(), |e| start + e);
+            let (token, after) = (&rest[start..end], &rest[end..]);
+            let open = after.trim_start_cpp_whitespace_comments();
+            if !StaticAssert::TYPE_OPERATORS.contains(&token) || !open.starts_with('(') {
+                out.push_str(token);
+                rest = after;
+                continue
+            }
+
+            let mut args = vec![String::new()];
+            let mut depth = 0;
+            let mut close = None;
+            for (i, ch) in open.char_indices() {
+                match ch {
+                    '(' if depth == 0   => { depth += 1; continue },
+                    '(' | '['           => depth += 1,
+                    ')' | ']'           => { depth -= 1; if depth == 0 { close = Some(i); break } },
+                    ',' if depth == 1   => { args.push(String::new()); continue },
+                    _                   => {},
+                }
+                args.last_mut().unwrap().push(ch);
+            }
+            rest = &open[close?+1..];
+            let args = args.iter().map(|a| a.trim()).collect::<Vec<_>>();
+
+            let value = match (token, args.as_slice()) {
+                ("sizeof", [ty])                                                => self.size_align_of(ty, arch).ok()?.0,
+                ("TYPE_ALIGNMENT" | "__alignof" | "_alignof" | "alignof", [ty]) => self.size_align_of(ty, arch).ok()?.1,
+                ("FIELD_OFFSET" | "offsetof", [ty, field])                      => self.layout(ty.trim_start_matches("struct ").trim(), arch).ok()?.offset_of(field)?,
+                ("RTL_FIELD_SIZE", [ty, field])                                 => self.layout(ty.trim_start_matches("struct ").trim(), arch).ok()?.find_field(field)?.1.size,
+                _                                                               => return None,
+            };
+            out.push_str(&format!(" {} ", value));
+        }
+        out.push_str(rest);
+        self.eval(&out)
+    }
+
+    fn aggregate(&self, id: &str, depth: usize) -> Option<&AggregateData> {
+        if let Some(agg) = self.structs.get(id).or_else(|| self.unions.get(id)).or_else(|| self.classes.get(id)) { return Some(&agg.data) }
+        if depth >= MAX_DEPTH { return None }
+        let ty = self.typedefs.get(id)?;
+        self.aggregate(ty.trim_start_matches("struct ").trim_start_matches("union ").trim_start_matches("class "), depth + 1)
+    }
+
+    pub(crate) fn layout_aggregate(&self, agg: &AggregateData, arch: Arch, depth: usize) -> Result<Layout, String> {
+        if depth >= MAX_DEPTH { return Err("types nested too deeply (recursive type?)".into()) }
+        let union = agg.category == AggregateCategory::Union;
+        let pack  = |align: u64| agg.pack.map_or(align, |pack| align.min(pack.into()));
+
+        let mut layout = Layout { size: 0, align: 1, fields: Vec::new(), _non_exhaustive: () };
+        let mut offset = 0;
+        if let Some(base) = agg.base.as_ref() {
+            // MSVC places the base subobject first, and doesn't reuse it's tail padding
+            let (size, align) = self.size_align_of_impl(base, arch, depth + 1).map_err(|err| format!("base `{}`: {}", base, err))?;
+            offset = size;
+            layout.size = size;
+            layout.align = align;
+        }
+        let mut unit = None; // (storage_unit, offset)
+
+        for field in agg.fields.values_by_insert() {
+            let field = match self.field_for_arch(field, arch)? { Some(field) => field, None => continue };
+            if field.bits_expr.is_some() {
+                let bit_layout = match field.bit_layout {
+                    Some(l) => l,
+                    None if field.bits.is_none() && self.eval(field.bits_expr.as_deref().unwrap_or("")) == Some(0) => { unit = None; continue }, // `UINT : 0;`
+                    None => return Err(format!("unable to lay out bitfield `{}`", field.id)),
+                };
+                let size  = u64::from(bit_layout.storage_size);
+                let align = pack(size).max(field.align.map_or(1, u64::from));
+                let field_offset = match unit {
+                    Some((storage_unit, unit_offset)) if storage_unit == bit_layout.storage_unit => unit_offset,
+                    _ => {
+                        let unit_offset = if union { 0 } else { round_up(offset, align) };
+                        offset = unit_offset + size;
+                        unit = Some((bit_layout.storage_unit, unit_offset));
+                        unit_offset
+                    },
+                };
+                layout.size  = layout.size.max(field_offset + size);
+                layout.align = layout.align.max(align);
+                layout.fields.push(FieldLayout { id: field.id.clone(), offset: field_offset, size, align, bit_layout: Some(bit_layout), nested: None, _non_exhaustive: () });
+                continue
+            }
+            unit = None;
+
+            let (size, align, nested) = match &field.ty {
+                Type::Basic(ty)     => { let (size, align) = self.size_align_of_impl(ty, arch, depth + 1).map_err(|err| format!("field `{}`: {}", field.id, err))?; (size, align, None) },
+                Type::Enum(_)       => (4, 4, None),
+                Type::Aggregate(a)  => { let l = self.layout_aggregate(a, arch, depth + 1)?; (l.size, l.align, Some(l)) },
+            };
+            let mut count = 1;
+            for dim in field.array.iter() {
+                let n = if dim.is_empty() { 0 } else { self.eval(dim).and_then(|n| u64::try_from(n).ok()).ok_or_else(|| format!("field `{}`: unable to evaluate array length `{}`", field.id, dim))? };
+                count *= n;
+            }
+            let size = size * count;
+            let align = pack(align).max(field.align.map_or(1, u64::from));
+
+            let field_offset = if union { 0 } else { round_up(offset, align) };
+            offset = field_offset + size;
+            layout.size  = layout.size.max(offset);
+            layout.align = layout.align.max(align);
+            layout.fields.push(FieldLayout { id: field.id.clone(), offset: field_offset, size, align, bit_layout: None, nested, _non_exhaustive: () });
+        }
+
+        layout.align = layout.align.max(agg.align.map_or(1, u64::from));
+        layout.size = round_up(layout.size, layout.align);
+        Ok(layout)
+    }
+
+    /// Select `field` or one of it's [`Field::variants`], whichever is declared under a preprocessor condition that holds for `arch`, if any.
+    fn field_for_arch<'f>(&self, field: &'f Field, arch: Arch) -> Result<Option<&'f Field>, String> {
+        for f in std::iter::once(field).chain(field.variants.iter()) {
+            let holds = match f.condition.as_deref() {
+                None    => true,
+                Some(c) => arch.eval_condition_in(c, self).ok_or_else(|| format!("field `{}`: unable to evaluate condition `{}`", f.id, c))?,
+            };
+            if holds { return Ok(Some(f)) }
+        }
+        Ok(None)
+    }
+
+    fn size_align_of_impl(&self, ty: &str, arch: Arch, depth: usize) -> Result<(u64, u64), String> {
+        if depth >= MAX_DEPTH { return Err(format!("type `{}` nested too deeply (recursive typedef?)", ty)) }
+
+        let ty = strip_qualifiers(ty);
+        let ptr = arch.pointer_size();
+        if ty.contains('*') || ty.contains('&') { return Ok((ptr, ptr)) }
+        if let Some(size) = self.integer_size(&ty) { return Ok((size.into(), size.into())) }
+
+        Ok(match ty.as_str() {
+            "float" | "FLOAT"                                                       => (4, 4),
+            "double" | "DOUBLE" | "long double"                                     => (8, 8),
+            "GUID" | "IID" | "CLSID" | "UUID" | "FMTID"                             => (16, 4),
+            "size_t" | "ptrdiff_t" | "intptr_t" | "uintptr_t" | "SIZE_T" | "SSIZE_T"
+            | "INT_PTR" | "UINT_PTR" | "LONG_PTR" | "ULONG_PTR" | "DWORD_PTR"
+            | "WPARAM" | "LPARAM" | "LRESULT" | "FARPROC"                           => (ptr, ptr),
+            ty => {
+                if let Some(agg) = self.aggregate(ty, 0) {
+                    let l = self.layout_aggregate(agg, arch, depth + 1)?;
+                    (l.size, l.align)
+                } else if let Some(ty) = self.typedefs.get(ty) {
+                    self.size_align_of_impl(ty, arch, depth + 1)?
+                } else {
+                    return Err(format!("unknown size of type `{}`", ty))
+                }
+            },
+        })
+    }
+}
+
+fn round_up(n: u64, align: u64) -> u64 { n.div_ceil(align) * align }
+
+/// Strip `const`, `volatile`, `struct`, SAL annotations such as `_Field_size_(n)`, etc. from a type.
+fn strip_qualifiers(ty: &str) -> String {
+    let mut out = Vec::new();
+    let mut tokens = ty.split_ascii_whitespace().peekable();
+    while let Some(token) = tokens.next() {
+        let sal = token.len() > 2 && token.starts_with('_') && token.ends_with('_') && !token.starts_with("__");
+        if sal {
+            if tokens.peek() == Some(&"(") {
+                let mut depth = 0;
+                for token in tokens.by_ref() {
+                    match token { "(" => depth += 1, ")" => depth -= 1, _ => {} }
+                    if depth == 0 { break }
+                }
+            }
+            continue
+        }
+        if ["const", "volatile", "struct", "union", "class", "enum", "__unaligned", "UNALIGNED"].contains(&token) { continue }
+        out.push(token);
+    }
+    out.join(" ")
+}
+
+
+
+#[test] fn test_layout() {
+    let root = Root::from_cpp_src(r#"
+        typedef UINT64 D3D12_GPU_VIRTUAL_ADDRESS;
+        typedef enum D3D12_RAYTRACING_INSTANCE_FLAGS { D3D12_RAYTRACING_INSTANCE_FLAG_NONE = 0 } D3D12_RAYTRACING_INSTANCE_FLAGS;
+
+        typedef struct D3D12_RAYTRACING_INSTANCE_DESC {
+            FLOAT Transform[3][4];
+            UINT InstanceID : 24;
+            UINT InstanceMask : 8;
+            UINT InstanceContributionToHitGroupIndex : 24;
+            UINT Flags : 8;
+            D3D12_GPU_VIRTUAL_ADDRESS AccelerationStructure;
+        } D3D12_RAYTRACING_INSTANCE_DESC;
+
+        typedef struct D3D12_SUBRESOURCE_DATA {
+            const void *pData;
+            LONG_PTR RowPitch;
+            BYTE Tag;
+        } D3D12_SUBRESOURCE_DATA;
+
+        typedef struct _Variant {
+            WORD vt;
+            union {
+                BYTE bVal;
+                LONGLONG llVal;
+                struct { WORD a; WORD b; } DUMMYSTRUCTNAME;
+            } DUMMYUNIONNAME;
+            D3D12_SUBRESOURCE_DATA Data;
+        } Variant;
+    "#);
+
+    for arch in Arch::ALL.iter().copied() {
+        let desc = root.layout("D3D12_RAYTRACING_INSTANCE_DESC", arch).unwrap();
+        assert_eq!((desc.size, desc.align), (64, 8));
+        let offsets = desc.fields.iter().map(|f| (f.id.as_str(), f.offset, f.size)).collect::<Vec<_>>();
+        assert_eq!(offsets, [
+            ("Transform",                               0, 48),
+            ("InstanceID",                             48,  4),
+            ("InstanceMask",                           48,  4),
+            ("InstanceContributionToHitGroupIndex",    52,  4),
+            ("Flags",                                  52,  4),
+            ("AccelerationStructure",                  56,  8),
+        ]);
+    }
+
+    let x86 = root.layout("D3D12_SUBRESOURCE_DATA", Arch::X86).unwrap();
+    let x64 = root.layout("D3D12_SUBRESOURCE_DATA", Arch::X64).unwrap();
+    assert_eq!((x86.size, x86.align, x86.offset_of("Tag")), (12, 4, Some( 8)));
+    assert_eq!((x64.size, x64.align, x64.offset_of("Tag")), (24, 8, Some(16)));
+
+    let variant = root.layout("Variant", Arch::X86).unwrap();
+    assert_eq!((variant.size, variant.align), (32, 8));
+    assert_eq!(variant.offset_of("llVal"), Some(8));
+    assert_eq!(variant.offset_of("b"),     Some(10));
+    assert_eq!(variant.offset_of("Data"),  Some(16));
+
+    assert!(root.layouts(Arch::ARM64).values().all(|l| l.is_ok()));
+    assert!(root.layout("Missing", Arch::X64).is_err());
+}
+
+#[test] fn test_layout_handles() {
+    let root = Root::from_cpp_src(r#"
+        typedef void *HANDLE;
+        typedef void *LPVOID;
+        DECLARE_HANDLE(HWND);
+
+        typedef struct Handles { char c; HANDLE h; HWND hwnd; LPVOID p; } Handles;
+        typedef struct Unknown { char c; HALF h; } Unknown;
+        typedef struct UnknownPointer { char c; LPUNKNOWNTHING p; } UnknownPointer;
+    "#);
+
+    let handles = root.layout("Handles", Arch::X64).unwrap();
+    assert_eq!((handles.size, handles.align, handles.offset_of("hwnd")), (32, 8, Some(16)));
+    assert_eq!(root.layout("Unknown", Arch::X64).unwrap_err(), "field `h`: unknown size of type `HALF`");
+    assert_eq!(root.layout("UnknownPointer", Arch::X64).unwrap_err(), "field `p`: unknown size of type `LPUNKNOWNTHING`");
+}
+
+#[test] fn test_layout_pack_align() {
+    let root = Root::from_cpp_src(r#"
+        #pragma pack(push, 1)
+        typedef struct XAUDIO2_VOICE_DETAILS {
+            UINT32 CreationFlags;
+            UINT32 ActiveFlags;
+            BYTE InputChannels;
+            UINT32 InputSampleRate;
+        } XAUDIO2_VOICE_DETAILS;
+        #pragma pack(pop)
+
+        #include <pshpack4.h>
+        typedef struct Packed4 { BYTE a; UINT64 b; } Packed4;
+        #include <poppack.h>
+
+        #ifdef _WIN64
+        #include <pshpack1.h>
+        #else
+        #include <pshpack1.h>
+        #endif
+        typedef struct Packed1 { BYTE a; DWORD b; } Packed1;
+        #include <poppack.h>
+        typedef struct Unpacked { BYTE a; DWORD b; } Unpacked;
+
+        typedef struct DECLSPEC_ALIGN(16) _M128A {
+            ULONGLONG Low;
+            LONGLONG High;
+        } M128A;
+
+        typedef struct _XSAVE_LIKE {
+            WORD ControlWord;
+            DECLSPEC_ALIGN(32) BYTE Aligned;
+            M128A Xmm0;
+        } XSAVE_LIKE;
+    "#);
+
+    let details = root.structs.get("XAUDIO2_VOICE_DETAILS").unwrap();
+    assert_eq!(details.pack, Some(1));
+    let details = root.layout("XAUDIO2_VOICE_DETAILS", Arch::X64).unwrap();
+    assert_eq!((details.size, details.align, details.offset_of("InputSampleRate")), (13, 1, Some(9)));
+
+    let packed4 = root.layout("Packed4", Arch::X86).unwrap();
+    assert_eq!((packed4.size, packed4.align, packed4.offset_of("b")), (12, 4, Some(4)));
+    assert_eq!(root.layout("Packed1",  Arch::X64).unwrap().size, 5);
+    assert_eq!(root.layout("Unpacked", Arch::X64).unwrap().size, 8);
+
+    let m128a = root.structs.get("M128A").unwrap();
+    assert_eq!((m128a.pack, m128a.align), (None, Some(16)));
+
+    let xsave = root.structs.get("XSAVE_LIKE").unwrap();
+    let aligned = xsave.fields.get("Aligned").unwrap();
+    assert_eq!(aligned.align, Some(32));
+    assert!(matches!(&aligned.ty, Type::Basic(ty) if ty.as_str() == "BYTE"));
+
+    let xsave = root.layout("XSAVE_LIKE", Arch::X86).unwrap();
+    assert_eq!((xsave.size, xsave.align, xsave.offset_of("Aligned"), xsave.offset_of("Xmm0")), (64, 32, Some(32), Some(48)));
+}
+
+#[test] fn test_layout_conditional_fields() {
+    let root = Root::from_cpp_src(r#"
+        #define DIRECT3D_VERSION 0x0900
+        typedef struct S {
+        #ifdef _WIN64
+            UINT64 a;
+        #else
+            UINT32 b;
+        #endif
+        #if defined(_M_ARM64)
+            UINT32 c;
+        #elif (DIRECT3D_VERSION >= 0x0900)
+            UINT64 c;
+        #endif
+        } S;
+
+        typedef struct Unknown {
+        #ifdef FOO
+            UINT64 a;
+        #endif
+        } Unknown;
+    "#);
+
+    let offsets = |arch| root.layout("S", arch).map(|l| (l.size, l.fields.iter().map(|f| (f.id.to_string(), f.offset, f.size)).collect::<Vec<_>>()));
+    let f = |fields: &[(&str, u64, u64)]| fields.iter().map(|(id, offset, size)| (id.to_string(), *offset, *size)).collect::<Vec<_>>();
+    assert_eq!(offsets(Arch::X86),      Ok((16, f(&[("b", 0, 4), ("c", 8, 8)]))));
+    assert_eq!(offsets(Arch::X64),      Ok((16, f(&[("a", 0, 8), ("c", 8, 8)]))));
+    assert_eq!(offsets(Arch::ARM64),    Ok((16, f(&[("a", 0, 8), ("c", 8, 4)]))));
+    assert_eq!(root.layout("Unknown", Arch::X64), Err("field `a`: unable to evaluate condition `defined(FOO)`".into()));
+}
+
+#[test] fn test_arch_conditions() {
+    let eval = |cond: &str| Arch::ALL.iter().map(|arch| arch.eval_condition(cond)).collect::<Vec<_>>();
+    assert_eq!(eval("defined(_WIN64)"),                         [Some(false), Some(true),  Some(true) ]);
+    assert_eq!(eval("!defined(_WIN64)"),                        [Some(true),  Some(false), Some(false)]);
+    assert_eq!(eval("defined _M_IX86 || defined(_M_ARM64)"),    [Some(true),  Some(false), Some(true) ]);
+    assert_eq!(eval("(_WIN64) && !defined(_M_ARM64)"),          [Some(false), Some(true),  Some(false)]);
+    assert_eq!(eval("(DIRECT3D_VERSION >= 0x0900)"),            [None,        None,        None       ]);
+    assert_eq!(eval("defined(_WIN64) && defined(FOO)"),         [None,        None,        None       ]);
+}
@@ -0,0 +1,95 @@
+use crate::*;
+
+
+
+/// A calling convention shape of a [`Method`] that wrapper generators can map to something more idiomatic, as returned by [`Method::pattern`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MethodPattern {
+    /// `HRESULT QueryInterface(REFIID riid, _COM_Outptr_ void** ppvObject)` - returns an interface of the caller's choosing.
+    ///
+    /// `riid` and `ppv` are the indices of the parameters in [`Method::params`].
+    /// This also matches e.g. `ID3D12Device::CreateCommittedResource(..., REFIID riidResource, _COM_Outptr_opt_ void** ppvResource)`.
+    QueryInterface { riid: usize, ppv: usize },
+
+    /// `HRESULT GetDevice(_COM_Outptr_ ID3D12Device** ppDevice)` - returns a `ty*` (e.g. `ID3D12Device`) via the last parameter.
+    ///
+    /// `optional` is set for `_COM_Outptr_opt_` and similar annotations, where the caller may pass `NULL`.
+    OutPtr { ty: String, optional: bool },
+
+    /// `HRESULT GetDesc(_Out_ DXGI_ADAPTER_DESC* pDesc)` - returns a `ty` (e.g. `DXGI_ADAPTER_DESC`) via the last parameter.
+    RetVal { ty: String },
+}
+
+impl Method {
+    /// Recognize the [`MethodPattern`] of this method, if any.
+    ///
+    /// Only `HRESULT`-returning methods are considered.
+    /// Unannotated `T**` parameters (common in older headers such as `d3d9.h`) are treated as out pointers, but unannotated `T*` parameters are not treated as return values.
+    pub fn pattern(&self) -> Option<MethodPattern> {
+        if self.ret.as_deref()?.split_whitespace().last() != Some("HRESULT") { return None }
+        let last = self.params.len().checked_sub(1)?;
+        let param = &self.params[last];
+        let ty = param_type(param);
+        let annotations = param.split(|ch: char| !ch.is_ascii_word_character()).filter(|w| w.starts_with('_')).collect::<Vec<_>>();
+
+        if ty == "void**" && last > 0 && ["REFIID", "const IID&", "REFGUID", "const GUID&"].contains(&&*param_type(&self.params[last-1])) {
+            return Some(MethodPattern::QueryInterface { riid: last-1, ppv: last });
+        }
+
+        if ty.starts_with("const ") || ty.contains('[') { return None }
+        let outptr = annotations.iter().find(|a| a.starts_with("_COM_Outptr") || a.starts_with("_Outptr"));
+        if let Some(pointee) = ty.strip_suffix("**").filter(|t| !t.ends_with('*') && *t != "void") {
+            if annotations.is_empty() || outptr.is_some() {
+                let optional = outptr.is_some_and(|a| a.contains("_opt_") || a.contains("maybenull"));
+                return Some(MethodPattern::OutPtr { ty: pointee.into(), optional });
+            }
+        }
+
+        let pointee = ty.strip_suffix('*').filter(|t| !t.ends_with('*') && *t != "void")?;
+        annotations.contains(&"_Out_").then(|| MethodPattern::RetVal { ty: pointee.into() })
+    }
+}
+
+
+
+#[test] fn test_method_patterns() {
+    let root = Root::from_cpp_src(r#"
+        MIDL_INTERFACE("00000000-0000-0000-0000-000000000001")
+        IPatterns : public IUnknown
+        {
+        public:
+            virtual HRESULT STDMETHODCALLTYPE QueryInterface(REFIID riid, _COM_Outptr_ void **ppvObject) = 0;
+            virtual HRESULT STDMETHODCALLTYPE CreateCommittedResource(_In_ const D3D12_HEAP_PROPERTIES *pHeapProperties, REFIID riidResource, _COM_Outptr_opt_ void **ppvResource) = 0;
+            virtual HRESULT STDMETHODCALLTYPE GetDevice(_COM_Outptr_ ID3D12Device **ppDevice) = 0;
+            virtual HRESULT STDMETHODCALLTYPE GetParent(_COM_Outptr_opt_result_maybenull_ IDXGIObject **ppParent) = 0;
+            virtual HRESULT STDMETHODCALLTYPE GetDesc(_Out_ DXGI_ADAPTER_DESC *pDesc) = 0;
+            virtual HRESULT STDMETHODCALLTYPE SetDesc(_In_ const DXGI_ADAPTER_DESC *pDesc) = 0;
+            virtual HRESULT STDMETHODCALLTYPE GetData(_Out_writes_bytes_(DataSize) void *pData, UINT DataSize) = 0;
+            virtual HRESULT STDMETHODCALLTYPE SetPrivateData(REFGUID guid, UINT DataSize, _In_reads_bytes_opt_(DataSize) const void *pData) = 0;
+            virtual UINT STDMETHODCALLTYPE GetCount(_Out_ UINT *pCount) = 0;
+            virtual HRESULT STDMETHODCALLTYPE Present( void) = 0;
+        };
+
+        DECLARE_INTERFACE_(IDirect3D9, IUnknown)
+        {
+            STDMETHOD(CreateDevice)(THIS_ UINT Adapter,HWND hFocusWindow,IDirect3DDevice9** ppReturnedDeviceInterface) PURE;
+            STDMETHOD(CheckDeviceType)(THIS_ UINT Adapter,D3DFORMAT* pFormat) PURE;
+        };
+    "#);
+
+    let patterns = root.interfaces.values_by_insert().flat_map(|i| i.all_methods.iter()).map(|m| (m.f.id.as_str(), m.pattern())).collect::<Vec<_>>();
+    assert_eq!(patterns, [
+        ("QueryInterface",          Some(MethodPattern::QueryInterface { riid: 0, ppv: 1 })),
+        ("CreateCommittedResource", Some(MethodPattern::QueryInterface { riid: 1, ppv: 2 })),
+        ("GetDevice",               Some(MethodPattern::OutPtr { ty: "ID3D12Device".into(), optional: false })),
+        ("GetParent",               Some(MethodPattern::OutPtr { ty: "IDXGIObject".into(), optional: true })),
+        ("GetDesc",                 Some(MethodPattern::RetVal { ty: "DXGI_ADAPTER_DESC".into() })),
+        ("SetDesc",                 None),
+        ("GetData",                 None),
+        ("SetPrivateData",          None),
+        ("GetCount",                None),
+        ("Present",                 None),
+        ("CreateDevice",            Some(MethodPattern::OutPtr { ty: "IDirect3DDevice9".into(), optional: false })),
+        ("CheckDeviceType",         None),
+    ]);
+}
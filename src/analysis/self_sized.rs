@@ -0,0 +1,106 @@
+use crate::*;
+
+use std::collections::*;
+use std::fmt::{self, Debug, Formatter};
+
+
+
+/// A `struct` whose first field must be initialized to `sizeof` the struct, such as `WNDCLASSEXW::cbSize` or `DIDEVCAPS::dwSize`.
+///
+/// Found by [`Root::self_sized_structs`].
+pub struct SelfSizedStruct {
+    /// The struct (e.g. `WNDCLASSEXW`)
+    pub id:                     Ident,
+
+    /// The field that holds the size (e.g. `cbSize`)
+    pub size_field:             Ident,
+
+    /// The type of [`size_field`](Self::size_field) (e.g. `UINT`)
+    pub size_type:              Ident,
+
+    /// The expected value of [`size_field`](Self::size_field) per architecture, for those architectures the struct could be laid out for.
+    pub sizes:                  BTreeMap<Arch, u64>,
+
+    pub(crate) _non_exhaustive: (),
+}
+
+impl Debug for SelfSizedStruct {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("SelfSizedStruct")
+            .field("id",            &self.id            )
+            .field("size_field",    &self.size_field    )
+            .field("size_type",     &self.size_type     )
+            .field("sizes",         &self.sizes         )
+            .finish_non_exhaustive()
+    }
+}
+
+
+
+impl Root {
+    /// Find `struct`s whose first field holds their own size (`cbSize`, `dwSize`, `Size`, ...), in definition order.
+    ///
+    /// This is a naming heuristic:  the first field must have a conventional size name and an integer type.
+    /// Structs with a base type are skipped, as their first field belongs to the base.
+    pub fn self_sized_structs(&self) -> Vec<SelfSizedStruct> {
+        const SIZE_NAMES : &[&str] = &["cbSize", "dwSize", "Size", "cbStruct", "cbStructSize", "dwStructSize", "lStructSize", "nSize", "uSize", "StructSize"];
+
+        self.structs.values_by_insert().filter_map(|s| {
+            if s.base.is_some() { return None }
+            let field = s.fields.values_by_insert().next()?;
+            if !SIZE_NAMES.contains(&field.id.as_str()) || !field.array.is_empty() || field.bits.is_some() || !field.variants.is_empty() { return None }
+            let ty = match &field.ty { Type::Basic(ty) => ty, _ => return None };
+            let integer = ty.as_str() == "SIZE_T" || ty.as_str() == "size_t" || self.integer_size(ty).is_some_and(|size| size >= 2);
+            if !integer || self.enums.get(ty.as_str()).is_some() { return None }
+
+            Some(SelfSizedStruct {
+                id:                 s.id.clone(),
+                size_field:         field.id.clone(),
+                size_type:          ty.clone(),
+                sizes:              Arch::ALL.iter().filter_map(|&arch| Some((arch, self.layout(&s.id, arch).ok()?.size))).collect(),
+                _non_exhaustive:    (),
+            })
+        }).collect()
+    }
+}
+
+
+
+#[test] fn test_self_sized_structs() {
+    let root = Root::from_cpp_src(r#"
+        typedef struct tagWNDCLASSEXW {
+            UINT        cbSize;
+            UINT        style;
+            WNDPROC     lpfnWndProc;
+            int         cbClsExtra;
+        } WNDCLASSEXW, *PWNDCLASSEXW;
+
+        typedef struct DIDEVCAPS {
+            DWORD   dwSize;
+            DWORD   dwFlags;
+        } DIDEVCAPS, *LPDIDEVCAPS;
+
+        typedef struct D3D12_RANGE {
+            SIZE_T Begin;
+            SIZE_T End;
+        } D3D12_RANGE;
+
+        typedef struct SIZE_ARRAY {
+            BYTE Size[4];
+        } SIZE_ARRAY;
+
+        typedef enum SIZE_KIND { SIZE_KIND_SMALL } SIZE_KIND;
+        typedef struct SIZED_BY_ENUM {
+            SIZE_KIND Size;
+        } SIZED_BY_ENUM;
+    "#);
+
+    let found = root.self_sized_structs();
+    let summary = found.iter().map(|s| (s.id.as_str(), s.size_field.as_str(), s.size_type.as_str())).collect::<Vec<_>>();
+    assert_eq!(summary, [
+        ("WNDCLASSEXW", "cbSize",   "UINT"),
+        ("DIDEVCAPS",   "dwSize",   "DWORD"),
+    ]);
+    assert!(found[0].sizes.is_empty()); // WNDPROC is unknown
+    assert_eq!(found[1].sizes.get(&Arch::X64), Some(&8));
+}
@@ -0,0 +1,181 @@
+use crate::*;
+
+use std::collections::*;
+use std::fmt::{self, Debug, Formatter};
+
+
+
+/// A family of numbered versions of a type, such as `IDXGIFactory` .. `IDXGIFactory7`, or `DXGI_SWAP_CHAIN_DESC` / `DXGI_SWAP_CHAIN_DESC1`.
+///
+/// Found by [`Root::version_families`].
+pub struct VersionFamily {
+    /// The unnumbered name of the family (e.g. `IDXGIFactory`), which may or may not itself be a member.
+    pub base_name:              String,
+
+    pub kind:                   VersionFamilyKind,
+
+    /// The members of this family, ordered by version.
+    pub members:                Vec<VersionMember>,
+
+    /// Versions missing between the first and last member (e.g. `[2]` if only `ID3D12Foo`, `ID3D12Foo1`, and `ID3D12Foo3` exist.)
+    pub gaps:                   Vec<u32>,
+
+    pub(crate) _non_exhaustive: (),
+}
+
+/// A single version of a [`VersionFamily`].
+pub struct VersionMember {
+    /// The version number, or `0` for the unnumbered [`base_name`](VersionFamily::base_name).
+    pub version:                u32,
+
+    /// The type name (e.g. `IDXGIFactory2`)
+    pub id:                     Ident,
+
+    /// For interfaces after the first member:  does this interface (directly or indirectly) derive from the previous member?
+    /// [`None`] for the first member and non-interfaces.
+    pub derives_from_previous:  Option<bool>,
+
+    pub(crate) _non_exhaustive: (),
+}
+
+/// What kind of types a [`VersionFamily`] contains.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum VersionFamilyKind {
+    Interface,
+    Struct,
+    Union,
+    Enum,
+}
+
+impl Debug for VersionFamily {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("VersionFamily")
+            .field("base_name",     &self.base_name     )
+            .field("kind",          &self.kind          )
+            .field("members",       &self.members       )
+            .field("gaps",          &self.gaps          )
+            .finish_non_exhaustive()
+    }
+}
+
+impl Debug for VersionMember {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("VersionMember")
+            .field("version",               &self.version               )
+            .field("id",                    &self.id                    )
+            .field("derives_from_previous", &self.derives_from_previous )
+            .finish_non_exhaustive()
+    }
+}
+
+
+
+impl Root {
+    /// Group interfaces, structs, unions, and enums into numbered version families, ordered by kind, then base name.
+    ///
+    /// A family needs at least two members, one of which must be the unnumbered base name or version `1`.
+    /// This avoids treating e.g. `XMFLOAT2` / `XMFLOAT3` / `XMFLOAT4` as versions of each other.
+    pub fn version_families(&self) -> Vec<VersionFamily> {
+        let mut families = Vec::new();
+        families.extend(families_of(VersionFamilyKind::Interface,   self.interfaces .keys()));
+        families.extend(families_of(VersionFamilyKind::Struct,      self.structs    .keys()));
+        families.extend(families_of(VersionFamilyKind::Union,       self.unions     .keys()));
+        families.extend(families_of(VersionFamilyKind::Enum,        self.enums      .keys()));
+
+        for family in families.iter_mut().filter(|f| f.kind == VersionFamilyKind::Interface) {
+            for i in 1 .. family.members.len() {
+                let prev = family.members[i-1].id.clone();
+                family.members[i].derives_from_previous = Some(self.interface_derives_from(&family.members[i].id, &prev));
+            }
+        }
+        families
+    }
+
+    /// Does interface `id` derive from `ancestor`, directly or indirectly?
+    fn interface_derives_from(&self, id: &str, ancestor: &str) -> bool {
+        self.interface_bases(id).iter().any(|base| base.as_str() == ancestor)
+    }
+}
+
+fn families_of<'a>(kind: VersionFamilyKind, ids: impl Iterator<Item = &'a Ident>) -> Vec<VersionFamily> {
+    let mut by_base = BTreeMap::<&str, Vec<(u32, &Ident)>>::new();
+    for id in ids {
+        let (base, version) = split_version(id);
+        by_base.entry(base).or_default().push((version, id));
+    }
+
+    by_base.into_iter().filter_map(|(base, mut members)| {
+        members.sort();
+        if members.len() < 2 || members[0].0 > 1 { return None }
+        let versions = members.iter().map(|(v, _)| *v).collect::<BTreeSet<_>>();
+        let gaps = (members[0].0 ..= members[members.len()-1].0).filter(|v| !versions.contains(v)).collect();
+        Some(VersionFamily {
+            base_name:          base.into(),
+            kind,
+            members:            members.into_iter().map(|(version, id)| VersionMember { version, id: id.clone(), derives_from_previous: None, _non_exhaustive: () }).collect(),
+            gaps,
+            _non_exhaustive:    (),
+        })
+    }).collect()
+}
+
+/// Split `IDXGIFactory7` into `("IDXGIFactory", 7)`, or `IDXGIFactory` into `("IDXGIFactory", 0)`.
+fn split_version(id: &str) -> (&str, u32) {
+    let base = id.trim_end_matches(|ch: char| ch.is_ascii_digit());
+    if base.is_empty() || base.len() == id.len() { return (id, 0) }
+    match id[base.len()..].parse() {
+        Ok(v) if v > 0  => (base, v),
+        _               => (id, 0),
+    }
+}
+
+
+
+#[test] fn test_version_families() {
+    let root = Root::from_cpp_src(r#"
+        typedef struct DXGI_SWAP_CHAIN_DESC  { UINT BufferCount; } DXGI_SWAP_CHAIN_DESC;
+        typedef struct DXGI_SWAP_CHAIN_DESC1 { UINT Width; } DXGI_SWAP_CHAIN_DESC1;
+        typedef struct XMFLOAT2 { float x, y; } XMFLOAT2;
+        typedef struct XMFLOAT3 { float x, y, z; } XMFLOAT3;
+
+        MIDL_INTERFACE("7b7166ec-21c7-44ae-b21a-c9ae321ae369")
+        IDXGIFactory : public IDXGIObject
+        {
+        };
+
+        MIDL_INTERFACE("770aae78-f26f-4dba-a829-253c83d1b387")
+        IDXGIFactory1 : public IDXGIFactory
+        {
+        };
+
+        MIDL_INTERFACE("50c83a1c-e072-4c48-87b0-3630fa36a6d0")
+        IDXGIFactory2 : public IDXGIFactory1
+        {
+        };
+
+        MIDL_INTERFACE("25483823-cd46-4c7d-86ca-47aa95b837bd")
+        IDXGIFactory3 : public IDXGIFactory2
+        {
+        };
+
+        MIDL_INTERFACE("c1b6694f-ff09-44a9-b03c-77900a0a1d17")
+        IDXGIFactory5 : public IDXGIFactory3
+        {
+        };
+
+        MIDL_INTERFACE("c1b6694f-ff09-44a9-b03c-77900a0a1d18")
+        IDXGIFactory6 : public IUnknown
+        {
+        };
+    "#);
+
+    let families = root.version_families();
+    let summary = families.iter().map(|f| (f.kind, f.base_name.as_str(), f.members.iter().map(|m| m.version).collect::<Vec<_>>(), f.gaps.clone())).collect::<Vec<_>>();
+    assert_eq!(summary, [
+        (VersionFamilyKind::Interface,  "IDXGIFactory",         vec![0, 1, 2, 3, 5, 6], vec![4]),
+        (VersionFamilyKind::Struct,     "DXGI_SWAP_CHAIN_DESC", vec![0, 1],             vec![]),
+    ]);
+
+    let derives = families[0].members.iter().map(|m| m.derives_from_previous).collect::<Vec<_>>();
+    assert_eq!(derives, [None, Some(true), Some(true), Some(true), Some(true), Some(false)]);
+}
@@ -0,0 +1,103 @@
+use crate::*;
+
+use std::fmt::{self, Debug, Formatter};
+
+
+
+/// A single entry of an interface's vtable, as returned by [`Root::vtable`].
+pub struct VtableSlot<'a> {
+    /// The 0-based slot index (e.g. `0` for `IUnknown::QueryInterface`, `3` for the first method of an `IUnknown`-derived interface.)
+    pub index:                  usize,
+
+    /// The interface that declared this method, such as `IUnknown` for `AddRef`.
+    pub declared_by:            &'a Ident,
+
+    pub method:                 &'a Method,
+
+    pub(crate) _non_exhaustive: (),
+}
+
+impl Debug for VtableSlot<'_> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(fmt, "VtableSlot {{ index: {}, declared_by: {:?}, method: {:?}, .. }}", self.index, self.declared_by, self.method.f.id)
+    }
+}
+
+
+
+impl Root {
+    /// The complete vtable of interface `id` in slot order:  the root base interface's methods first, then each derived interface's own methods in declaration order.
+    ///
+    /// Returns [`None`] if `id` or any of it's bases are unknown, or if the bases are cyclic.
+    pub fn vtable(&self, id: &str) -> Option<Vec<VtableSlot<'_>>> {
+        let mut chain = vec![self.interfaces.get(id)?];
+        for base in self.interface_bases(id) { chain.push(self.interfaces.get(base)?); }
+        if chain.last()?.base.is_some() { return None } // cyclic bases
+
+        let methods = chain.iter().rev().flat_map(|i| msvc_vtable_order(&i.all_methods).into_iter().map(move |m| (&i.id, m)));
+        Some(methods.enumerate().map(|(index, (declared_by, method))| VtableSlot { index, declared_by, method, _non_exhaustive: () }).collect())
+    }
+}
+
+
+
+/// MSVC groups overloaded virtual methods together at the position of the first overload, in reverse declaration order.
+/// e.g. `virtual void f(int); virtual void g(); virtual void f(float);` results in `f(float), f(int), g()`.
+pub(crate) fn msvc_vtable_order(methods: &[Method]) -> Vec<&Method> {
+    let methods = methods.iter().filter(|m| !m.is_inherited()).collect::<Vec<_>>();
+    let mut ordered = Vec::with_capacity(methods.len());
+    for (i, method) in methods.iter().enumerate() {
+        if methods[..i].iter().any(|m| m.f.id == method.f.id) { continue } // already grouped
+        ordered.extend(methods[i..].iter().rev().filter(|m| m.f.id == method.f.id));
+    }
+    ordered
+}
+
+
+
+#[test] fn test_vtable() {
+    let root = Root::from_cpp_src(r#"
+        DECLARE_INTERFACE(IUnknown)
+        {
+            STDMETHOD(QueryInterface)(THIS_ REFIID riid, void** ppvObj) PURE;
+            STDMETHOD_(ULONG,AddRef)(THIS) PURE;
+            STDMETHOD_(ULONG,Release)(THIS) PURE;
+        };
+
+        DECLARE_INTERFACE_(IDirect3D9, IUnknown)
+        {
+            /*** IUnknown methods ***/
+            STDMETHOD(QueryInterface)(THIS_ REFIID riid, void** ppvObj) PURE;
+            STDMETHOD_(ULONG,AddRef)(THIS) PURE;
+            STDMETHOD_(ULONG,Release)(THIS) PURE;
+
+            /*** IDirect3D9 methods ***/
+            STDMETHOD(RegisterSoftwareDevice)(THIS_ void* pInitializeFunction) PURE;
+            STDMETHOD_(UINT, GetAdapterCount)(THIS) PURE;
+        };
+
+        DECLARE_INTERFACE_(IDirect3D9Ex, IDirect3D9)
+        {
+            STDMETHOD_(UINT, GetAdapterModeCountEx)(THIS_ UINT Adapter) PURE;
+        };
+
+        DECLARE_INTERFACE_(IOrphan, IMissing)
+        {
+            STDMETHOD(Orphan)(THIS) PURE;
+        };
+    "#);
+
+    let vtable = root.vtable("IDirect3D9Ex").unwrap();
+    let slots = vtable.iter().map(|s| (s.index, s.declared_by.as_str(), s.method.f.id.as_str())).collect::<Vec<_>>();
+    assert_eq!(slots, [
+        (0, "IUnknown",     "QueryInterface"),
+        (1, "IUnknown",     "AddRef"),
+        (2, "IUnknown",     "Release"),
+        (3, "IDirect3D9",   "RegisterSoftwareDevice"),
+        (4, "IDirect3D9",   "GetAdapterCount"),
+        (5, "IDirect3D9Ex", "GetAdapterModeCountEx"),
+    ]);
+
+    assert!(root.vtable("IOrphan").is_none());
+    assert!(root.vtable("IMissing").is_none());
+}
@@ -0,0 +1,155 @@
+use crate::*;
+
+
+
+/// The `#if` / `#ifdef` / `#elif` / `#else` / `#endif` nesting within a file, or within a `struct { ... }` or `enum { ... }` body.
+#[derive(Default)]
+pub(crate) struct Conditions {
+    stack: Vec<ConditionLevel>,
+}
+
+/// State (e.g. the `#pragma pack` stack) carried through `#if` / `#elif` / `#else` / `#endif`.
+///
+/// Each branch starts from the state at `#if`, and only the state at the end of the first branch carries on past `#endif`.
+pub(crate) struct FirstBranch<T> {
+    stack: Vec<(T, Option<T>)>,
+}
+
+struct ConditionLevel {
+    /// The conditions of previous `#if` / `#elif` branches at this level.
+    previous:   Vec<String>,
+
+    /// The condition of the current branch, or [`None`] for `#else`.
+    current:    Option<String>,
+}
+
+impl Conditions {
+    /// Apply a preprocessor directive (the text after `#`, e.g. `if (DIRECT3D_VERSION >= 0x0900)`.)
+    /// Returns `Ok(false)` if the directive wasn't a conditional (e.g. `#define`, `#pragma`.)
+    pub fn directive(&mut self, directive: &str) -> Result<bool, String> {
+        let directive = directive.without_cpp_comments();
+        let (keyword, rest) = split_directive(&directive);
+        match keyword {
+            "if"        => self.stack.push(ConditionLevel { previous: Vec::new(), current: Some(rest.into()) }),
+            "ifdef"     => self.stack.push(ConditionLevel { previous: Vec::new(), current: Some(format!("defined({})", rest)) }),
+            "ifndef"    => self.stack.push(ConditionLevel { previous: Vec::new(), current: Some(format!("!defined({})", rest)) }),
+            "elif" | "else" | "endif" => {
+                let level = self.stack.last_mut().ok_or_else(|| format!("`#{}` without matching `#if`", keyword))?;
+                match keyword {
+                    "endif" => { self.stack.pop(); },
+                    _ => {
+                        let prev = level.current.take().ok_or_else(|| format!("`#{}` after `#else`", keyword))?;
+                        level.previous.push(prev);
+                        if keyword == "elif" { level.current = Some(rest.into()); }
+                    },
+                }
+            },
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// The combined condition of all open levels (e.g. `(DIRECT3D_VERSION >= 0x0900) && !defined(NONAMELESSUNION)`), or [`None`] if unconditional.
+    pub fn current(&self) -> Option<String> {
+        let clauses = self.stack.iter().flat_map(|level| {
+            level.previous.iter().map(|p| negate(p)).chain(level.current.iter().map(|c| wrap(c)))
+        }).collect::<Vec<_>>();
+        if clauses.is_empty() { None } else { Some(clauses.join(" && ")) }
+    }
+
+    pub fn is_empty(&self) -> bool { self.stack.is_empty() }
+
+    /// `true` unless inside an `#elif` or `#else` branch at any level.
+    pub fn in_first_branch(&self) -> bool { self.stack.iter().all(|level| level.previous.is_empty()) }
+}
+
+impl<T> Default for FirstBranch<T> {
+    fn default() -> Self { Self { stack: Vec::new() } }
+}
+
+impl<T: Clone> FirstBranch<T> {
+    /// Apply a preprocessor directive (the text after `#`, e.g. `else`) to `state`, ignoring non-conditionals.
+    ///
+    /// Unmatched `#elif` / `#else` / `#endif`s are ignored here, and should be reported via [`Conditions`] instead.
+    pub fn directive(&mut self, directive: &str, state: &mut T) {
+        match split_directive(directive).0 {
+            "if" | "ifdef" | "ifndef" => self.stack.push((state.clone(), None)),
+            "elif" | "else" => if let Some((start, first)) = self.stack.last_mut() {
+                let end = std::mem::replace(state, start.clone());
+                first.get_or_insert(end);
+            },
+            "endif" => if let Some((_, Some(first))) = self.stack.pop() { *state = first },
+            _ => {},
+        }
+    }
+}
+
+/// Split e.g. `if (DIRECT3D_VERSION >= 0x0900)` into `if` and `(DIRECT3D_VERSION >= 0x0900)`.
+fn split_directive(directive: &str) -> (&str, &str) {
+    let directive = directive.trim();
+    directive.split_once(|ch: char| ch.is_ascii_whitespace() || ch == '(').map_or((directive, ""), |(k, _)| (k, directive[k.len()..].trim()))
+}
+
+/// Negate `cond`, simplifying e.g. `!defined(X)` to `defined(X)`.
+fn negate(cond: &str) -> String {
+    let cond = wrap(cond);
+    match cond.strip_prefix('!') {
+        Some(inner) if inner.starts_with("defined(") => inner.into(),
+        _ => format!("!{}", cond),
+    }
+}
+
+/// Wrap `cond` in parens unless it's a simple identifier or `defined(...)`.
+fn wrap(cond: &str) -> String {
+    let cond = strip_outer_parens(cond.trim());
+    let simple = cond.chars().all(|ch| ch.is_ascii_word_character()) || (cond.trim_start_matches('!').starts_with("defined(") && cond.ends_with(')') && cond.matches('(').count() == 1);
+    if simple { cond.into() } else { format!("({})", cond) }
+}
+
+fn strip_outer_parens(mut cond: &str) -> &str {
+    while let Some(inner) = cond.strip_prefix('(').and_then(|c| c.strip_suffix(')')) {
+        let mut depth = 0;
+        let balanced = inner.chars().all(|ch| {
+            match ch { '(' => depth += 1, ')' => depth -= 1, _ => {} }
+            depth >= 0
+        });
+        if !balanced { break }
+        cond = inner.trim();
+    }
+    cond
+}
+
+
+
+#[test] fn test_conditions() {
+    let mut c = Conditions::default();
+    assert_eq!(c.current(), None);
+    assert_eq!(c.directive("if (DIRECT3D_VERSION >= 0x0900) // comment"), Ok(true));
+    assert_eq!(c.current().as_deref(), Some("(DIRECT3D_VERSION >= 0x0900)"));
+    assert_eq!(c.directive("ifndef NONAMELESSUNION"), Ok(true));
+    assert_eq!(c.current().as_deref(), Some("(DIRECT3D_VERSION >= 0x0900) && !defined(NONAMELESSUNION)"));
+    assert_eq!(c.directive("endif"), Ok(true));
+    assert!(c.in_first_branch());
+    assert_eq!(c.directive("elif defined(_WIN64)"), Ok(true));
+    assert!(!c.in_first_branch());
+    assert_eq!(c.current().as_deref(), Some("!(DIRECT3D_VERSION >= 0x0900) && defined(_WIN64)"));
+    assert_eq!(c.directive("else"), Ok(true));
+    assert_eq!(c.current().as_deref(), Some("!(DIRECT3D_VERSION >= 0x0900) && !defined(_WIN64)"));
+    assert_eq!(c.directive("ifndef _WIN32"), Ok(true));
+    assert_eq!(c.directive("else"), Ok(true));
+    assert_eq!(c.current().as_deref(), Some("!(DIRECT3D_VERSION >= 0x0900) && !defined(_WIN64) && defined(_WIN32)"));
+    assert_eq!(c.directive("endif"), Ok(true));
+    assert_eq!(c.directive("define FOO 1"), Ok(false));
+    assert_eq!(c.directive("endif"), Ok(true));
+    assert!(c.is_empty());
+    assert!(c.directive("endif").is_err());
+
+    let mut b = FirstBranch::default();
+    let mut state = 1;
+    b.directive("ifdef FOO", &mut state);   state = 2;
+    b.directive("elif defined(BAR)", &mut state);   assert_eq!(state, 1);   state = 3;
+    b.directive("else", &mut state);        assert_eq!(state, 1);   state = 4;
+    b.directive("endif", &mut state);       assert_eq!(state, 2);
+    b.directive("if BAZ", &mut state);      state = 5;
+    b.directive("endif", &mut state);       assert_eq!(state, 5);
+}
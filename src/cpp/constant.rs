@@ -13,6 +13,9 @@ pub struct Constant {
     /// Identifier of this constant (e.g. `FOO`)
     pub id:                     Ident,
 
+    /// The value expression of this constant, sans comments (e.g. `1`)
+    pub value:                  Option<String>,
+
     pub(crate) _non_exhaustive: (),
 }
 
@@ -21,6 +24,7 @@ impl Constant {
         Self {
             defined_at: Default::default(),
             id: id.into(),
+            value: None,
             _non_exhaustive: Default::default(),
         }
     }
@@ -28,6 +32,6 @@ impl Constant {
 
 impl Debug for Constant {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "Constant {{ id: {:?}, .. }}", self.id)
+        write!(f, "Constant {{ id: {:?}, value: {:?}, .. }}", self.id, self.value)
     }
 }
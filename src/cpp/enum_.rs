@@ -20,7 +20,7 @@ pub struct Enum {
 #[derive(Default)]
 pub struct EnumData {
     pub class:                  bool,
-    pub values:                 VecMap<Ident, Option<String>>,
+    pub values:                 VecMap<Ident, Enumerator>,
     pub issues:                 Vec<Issue>,
     pub(crate) _non_exhaustive: (),
 }
@@ -112,40 +112,51 @@ impl EnumData {
         }}
 
         let mut warn_enumerand_name = false;
+        let mut conditions = Conditions::default();
+        let mut branches = FirstBranch::default();
+        let mut index = self.values.values_by_insert().flat_map(|e| std::iter::once(e).chain(e.variants.iter())).count();
+        let mut last = None; // Enumerator::index of the previous enumerator in the current branch
+
+        macro_rules! preprocessor { ( $token:expr ) => {{
+            let token = $token;
+            let rest_of_line = src.next_line();
+            let rest_of_line = rest_of_line.as_ref().map_or("", |l| &**l);
+            branches.directive(rest_of_line, &mut last);
+            match conditions.directive(rest_of_line) {
+                Ok(true) => {},
+                Ok(false) => self.issues.push(Issue::new(
+                    src.token_to_location(token),
+                    format!("preprocessor command inside `enum {{ ... }}` not supported: #{}", rest_of_line)
+                )),
+                Err(err) => self.issues.push(Issue::new(src.token_to_location(token), err)),
+            }
+        }}}
 
         'enum_: loop {
             let mut token = expect_token!();
             while token == "#" {
-                let rest_of_line = src.next_line();
-                let rest_of_line = rest_of_line.as_ref().map_or("", |l| &**l);
-                self.issues.push(Issue::new(
-                    src.token_to_location(token),
-                    format!("preprocessor command inside `enum {{ ... }}` not supported: #{}", rest_of_line)
-                ));
+                preprocessor!(token);
                 token = expect_token!();
             }
 
             if token == "}" { break 'enum_ }
 
-            let value = self.values.entry(Ident::own(&*token)).or_insert_with(|| None);
+            let mut enumerator = Enumerator::new(Ident::own(&token));
+            enumerator.condition = conditions.current();
+            enumerator.index = index;
+            enumerator.follows = last;
+            let mut end = false;
             let token = expect_token!();
             match &*token {
                 "=" => {
-                    let value = value.get_or_insert(Default::default());
+                    let mut value = String::new();
                     let mut parens = 0;
                     'value: loop {
                         let token = expect_token!();
                         match &*token {
-                            "#" => { // probably a preprocessor command
-                                let rest_of_line = src.next_line();
-                                let rest_of_line = rest_of_line.as_ref().map_or("", |l| &**l);
-                                self.issues.push(Issue::new(
-                                    src.token_to_location(token),
-                                    format!("preprocessor command inside `enum {{ ... }}` not supported: #{}", rest_of_line)
-                                ));
-                            },
+                            "#" => preprocessor!(token), // probably a preprocessor command
                             "," if parens == 0 => break 'value,
-                            "}" => break 'enum_,
+                            "}" => { end = true; break 'value },
                             ")" if parens == 0 => {
                                 let msg = "more closing `)` parens than opening `(` parens in enumerand value";
                                 let loc = src.token_to_location(token);
@@ -162,17 +173,11 @@ impl EnumData {
                             },
                         }
                     }
+                    enumerator.value = Some(value);
                 },
-                "#" => { // probably a preprocessor command
-                    let rest_of_line = src.next_line();
-                    let rest_of_line = rest_of_line.as_ref().map_or("", |l| &**l);
-                    self.issues.push(Issue::new(
-                        src.token_to_location(token),
-                        format!("preprocessor command inside `enum {{ ... }}` not supported: #{}", rest_of_line)
-                    ));
-                },
-                ","     => continue 'enum_,
-                "}"     => break 'enum_,
+                "#"     => preprocessor!(token), // probably a preprocessor command
+                ","     => {},
+                "}"     => end = true,
                 _       => {
                     if !warn_enumerand_name {
                         warn_enumerand_name = true;
@@ -184,18 +189,94 @@ impl EnumData {
                     let _ = src.next_line();
                 },
             }
+
+            match self.values.get_mut(&enumerator.id) {
+                None => { self.values.insert(enumerator.id.clone(), enumerator); },
+                Some(prev) if prev.condition != enumerator.condition && prev.variants.iter().all(|v| v.condition != enumerator.condition) => prev.variants.push(enumerator),
+                Some(_) => { self.issues.push(Issue::new(start.clone(), format!("duplicate enumerator `{}`", enumerator.id))); if end { break 'enum_ } continue 'enum_ },
+            }
+            last = Some(index);
+            index += 1;
+            if end { break 'enum_ }
         }
+        if !conditions.is_empty() { self.issues.push(Issue::new(start.clone(), "unterminated `#if` inside `enum { ... }`")); }
         // TODO: try to parse `name, name, name;` first?
         Ok(())
     }
 }
 
+impl EnumData {
+    /// Describe how `other` (e.g. a redefinition of the same type in another header) differs from `self`, enumerator by enumerator.
+    pub(crate) fn differences(&self, other: &EnumData) -> Vec<String> {
+        let mut diffs = Vec::new();
+        if self.class != other.class { diffs.push("`enum class` vs `enum`".into()); }
+
+        let a = self.values.values_by_insert().collect::<Vec<_>>();
+        let b = other.values.values_by_insert().collect::<Vec<_>>();
+        for i in 0 .. a.len().max(b.len()) {
+            match (a.get(i), b.get(i)) {
+                (Some(a), Some(b)) if a.id != b.id                      => diffs.push(format!("enumerator {} is `{}` vs `{}`", i, a.id, b.id)),
+                (Some(a), Some(b)) => {
+                    if !same_value(&a.value, &b.value) { diffs.push(format!("`{}` is `{}` vs `{}`", a.id, a.value.as_deref().unwrap_or("(implicit)"), b.value.as_deref().unwrap_or("(implicit)"))); }
+                    if a.condition != b.condition { diffs.push(format!("`{}` is declared under `{}` vs `{}`", a.id, a.condition.as_deref().unwrap_or("(no condition)"), b.condition.as_deref().unwrap_or("(no condition)"))); }
+                    let same_variants = a.variants.len() == b.variants.len() && a.variants.iter().zip(b.variants.iter()).all(|(a, b)| a.condition == b.condition && same_value(&a.value, &b.value));
+                    if !same_variants {
+                        let variants = |e: &Enumerator| if e.variants.is_empty() { "(none)".into() } else { e.variants.iter().map(|v| format!("{} (#if {})", v.value.as_deref().unwrap_or("(implicit)"), v.condition.as_deref().unwrap_or("1"))).collect::<Vec<_>>().join("; ") };
+                        diffs.push(format!("`{}` variants are `{}` vs `{}`", a.id, variants(a), variants(b)));
+                    }
+                },
+                (Some(a), None) => diffs.push(format!("enumerator `{}` missing from redefinition", a.id)),
+                (None, Some(b)) => diffs.push(format!("enumerator `{}` only in redefinition", b.id)),
+                (None, None)    => {},
+            }
+        }
+        diffs
+    }
+
+    /// Classify [`Enumerator::kind`]s based on their names and [`Enumerator::evaluated`] values.
+    pub(crate) fn classify_enumerators(&mut self) {
+        let ids = self.values.keys().cloned().collect::<BTreeSet<_>>();
+        let mut values = Vec::<(Ident, i64)>::new(); // previous EnumeratorKind::Value s
+        for e in self.values.values_by_insert_mut() {
+            let last_segment = e.id.rsplit('_').next().unwrap_or("");
+            let sibling_min = last_segment == "MAX" && ids.contains(&Ident::from(format!("{}MIN", &e.id[..e.id.len()-3])));
+            let past_values = e.evaluated.is_some_and(|v| values.iter().all(|(_, prev)| v >= *prev));
+
+            let force_suffix = ["_FORCE_DWORD", "_FORCE_UINT", "_FORCE_ULONG", "_FORCE_32BIT"].iter().any(|suffix| e.id.ends_with(suffix));
+
+            // `LAST`/`END` are deliberately not treated as counts: they're often real values (e.g. `STREAM_SEEK_END`)
+            e.kind = if force_suffix && e.evaluated.is_some_and(|v| [0x7FFF_FFFF, 0xFFFF_FFFF, -1].contains(&v)) {
+                EnumeratorKind::ForceSize
+            } else if !values.is_empty() && past_values && !sibling_min && (["COUNT", "MAX", "NUM"].contains(&last_segment) || e.id.contains("_NUM_")) {
+                EnumeratorKind::Count
+            } else if let Some(alias) = e.value.as_deref().filter(|v| *v != e.id.as_str()).and_then(|v| ids.get(v)) {
+                EnumeratorKind::Alias(alias.clone())
+            } else if let Some((alias, _)) = e.evaluated.and_then(|v| values.iter().find(|(_, prev)| *prev == v)) {
+                EnumeratorKind::Alias(alias.clone())
+            } else {
+                EnumeratorKind::Value
+            };
+
+            if let (EnumeratorKind::Value, Some(v)) = (&e.kind, e.evaluated) { values.push((e.id.clone(), v)); }
+        }
+    }
+}
+
+/// Compare enumerator value expressions, treating e.g. `1` and `0x1` as equal.
+fn same_value(a: &Option<String>, b: &Option<String>) -> bool {
+    match (a.as_deref(), b.as_deref()) {
+        (Some(a), Some(b))  => a == b || a.parse_cpp_int().is_some_and(|a| b.parse_cpp_int() == Some(a)),
+        (None, None)        => true,
+        _                   => false,
+    }
+}
+
 impl Debug for Enum {
     fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
         fmt.debug_struct("Enum")
             .field("id",            &self.id        )
             .field("class",         &self.class     )
-            .field("values",        &self.values    )
+            .field("values",        &self.values.values_by_insert().collect::<Vec<_>>())
             .field("issues",        &self.issues    )
             .finish_non_exhaustive()
     }
@@ -205,7 +286,7 @@ impl Debug for EnumData {
     fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
         fmt.debug_struct("EnumData")
             .field("class",         &self.class         )
-            .field("values",        &self.values        )
+            .field("values",        &self.values.values_by_insert().collect::<Vec<_>>())
             .field("issues",        &self.issues        )
             .finish_non_exhaustive()
     }
@@ -219,3 +300,133 @@ impl Deref for Enum {
 impl DerefMut for Enum {
     fn deref_mut(&mut self) -> &mut Self::Target { &mut self.data }
 }
+
+
+
+#[test] fn test_classify_enumerators() {
+    let root = Root::from_cpp_src(r#"
+        typedef enum _D3DBLENDOP {
+            D3DBLENDOP_ADD              = 1,
+            D3DBLENDOP_SUBTRACT         = 2,
+            D3DBLENDOP_MIN              = 4,
+            D3DBLENDOP_MAX              = 5,
+            D3DBLENDOP_FORCE_DWORD      = 0x7fffffff,
+        } D3DBLENDOP;
+
+        typedef enum _D3DSHOW {
+            SW_HIDE,
+            SW_SHOWNORMAL,
+            SW_NORMAL = SW_SHOWNORMAL,
+            SW_SHOWDEFAULT = 1,
+            SW_MAX,
+            SW_FORCE_UINT = 0xffffffff,
+        } D3DSHOW;
+
+        typedef enum D3D_SHADER_TYPE {
+            D3D_SHADER_TYPE_VERTEX,
+            D3D_SHADER_TYPE_PIXEL,
+            D3D_NUM_SHADER_TYPES,
+        } D3D_SHADER_TYPE;
+
+        typedef enum tagSTREAM_SEEK {
+            STREAM_SEEK_SET = 0,
+            STREAM_SEEK_CUR = 1,
+            STREAM_SEEK_END = 2,
+        } STREAM_SEEK;
+
+        typedef enum FOO_MODE {
+            FOO_MODE_NORMAL = 0,
+            FOO_MODE_FORCE_REFRESH = SOME_MACRO,
+            FOO_MODE_FORCE_DWORD = SOME_OTHER_MACRO,
+        } FOO_MODE;
+    "#);
+
+    let kinds = |e: &str| root.enums.get(e).unwrap().values.values_by_insert().map(|e| (e.id.to_string(), e.kind.clone())).collect::<Vec<_>>();
+    let alias = |id: &'static str| EnumeratorKind::Alias(Ident::from(id));
+    use EnumeratorKind::*;
+
+    assert_eq!(kinds("D3DBLENDOP"), [
+        ("D3DBLENDOP_ADD".into(),           Value),
+        ("D3DBLENDOP_SUBTRACT".into(),      Value),
+        ("D3DBLENDOP_MIN".into(),           Value),
+        ("D3DBLENDOP_MAX".into(),           Value),
+        ("D3DBLENDOP_FORCE_DWORD".into(),   ForceSize),
+    ]);
+
+    assert_eq!(kinds("D3DSHOW"), [
+        ("SW_HIDE".into(),                  Value),
+        ("SW_SHOWNORMAL".into(),            Value),
+        ("SW_NORMAL".into(),                alias("SW_SHOWNORMAL")),
+        ("SW_SHOWDEFAULT".into(),           alias("SW_SHOWNORMAL")),
+        ("SW_MAX".into(),                   Count),
+        ("SW_FORCE_UINT".into(),            ForceSize),
+    ]);
+
+    assert_eq!(kinds("D3D_SHADER_TYPE"), [
+        ("D3D_SHADER_TYPE_VERTEX".into(),   Value),
+        ("D3D_SHADER_TYPE_PIXEL".into(),    Value),
+        ("D3D_NUM_SHADER_TYPES".into(),     Count),
+    ]);
+
+    assert_eq!(kinds("tagSTREAM_SEEK"), [
+        ("STREAM_SEEK_SET".into(),          Value),
+        ("STREAM_SEEK_CUR".into(),          Value),
+        ("STREAM_SEEK_END".into(),          Value),
+    ]);
+
+    assert_eq!(kinds("FOO_MODE"), [
+        ("FOO_MODE_NORMAL".into(),          Value),
+        ("FOO_MODE_FORCE_REFRESH".into(),   Value),
+        ("FOO_MODE_FORCE_DWORD".into(),     Value),
+    ]);
+}
+
+
+#[test] fn test_conditional_enumerators() {
+    let root = Root::from_cpp_src(r#"
+        typedef enum _D3DRENDERSTATETYPE {
+            D3DRS_ZENABLE = 7,
+        #if(DIRECT3D_VERSION >= 0x0900)
+            D3DRS_SCISSORTESTENABLE = 174,
+            D3DRS_LAST,
+        #else
+            D3DRS_LAST = 100,
+        #endif
+            D3DRS_FORCE_DWORD = 0x7fffffff,
+        } D3DRENDERSTATETYPE;
+    "#);
+
+    let e = root.enums.get("D3DRENDERSTATETYPE").unwrap();
+    assert!(e.issues.is_empty(), "{:?}", e.issues);
+    let values = e.values.values_by_insert().map(|v| (v.id.as_str(), v.condition.as_deref(), v.evaluated)).collect::<Vec<_>>();
+    assert_eq!(values, [
+        ("D3DRS_ZENABLE",           None,                                   Some(7)),
+        ("D3DRS_SCISSORTESTENABLE", Some("(DIRECT3D_VERSION >= 0x0900)"),   Some(174)),
+        ("D3DRS_LAST",              Some("(DIRECT3D_VERSION >= 0x0900)"),   Some(175)),
+        ("D3DRS_FORCE_DWORD",       None,                                   Some(0x7fffffff)),
+    ]);
+
+    let last = e.values.get("D3DRS_LAST").unwrap();
+    assert_eq!(last.variants.len(), 1);
+    assert_eq!(last.variants[0].condition.as_deref(), Some("!(DIRECT3D_VERSION >= 0x0900)"));
+    assert_eq!(last.variants[0].evaluated, Some(100));
+
+    let root = Root::from_cpp_src(r#"
+        typedef enum BRANCHES {
+            A,
+        #if defined(FOO)
+            B,
+            C,
+        #else
+            D,
+            C,
+        #endif
+            Z,
+        } BRANCHES;
+    "#);
+    let e = root.enums.get("BRANCHES").unwrap();
+    assert!(e.issues.is_empty(), "{:?}", e.issues);
+    let values = e.values.values_by_insert().map(|v| (v.id.as_str(), v.evaluated)).collect::<Vec<_>>();
+    assert_eq!(values, [("A", Some(0)), ("B", Some(1)), ("C", Some(2)), ("D", Some(1)), ("Z", Some(3))]);
+    assert_eq!(e.values.get("C").unwrap().variants[0].evaluated, Some(2));
+}
@@ -0,0 +1,66 @@
+use crate::*;
+
+use std::fmt::{self, Debug, Formatter};
+
+
+
+/// `id = value,` as found inside an `enum`.
+pub struct Enumerator {
+    pub id:         Ident,
+
+    /// The value expression as written (e.g. `D3D12_RESOURCE_FLAG_NONE | 0x1`), or [`None`] if implicit.
+    pub value:      Option<String>,
+
+    /// The evaluated value, if it could be evaluated.
+    pub evaluated:  Option<i64>,
+
+    pub kind:       EnumeratorKind,
+
+    /// The preprocessor condition this enumerator was declared under, relative to the enclosing `enum { ... }` (e.g. `(DIRECT3D_VERSION >= 0x0900)`)
+    pub condition:  Option<String>,
+
+    /// Other declarations of this enumerator under different preprocessor conditions (e.g. the `#else` branch), in declaration order.
+    pub variants:   Vec<Enumerator>,
+
+    /// The declaration index of this enumerator (or variant) within it's `enum { ... }`.
+    pub(crate) index:   usize,
+
+    /// The [`Enumerator::index`] of the previous enumerator in the same `#if` branch, which an implicit value follows on from.
+    pub(crate) follows: Option<usize>,
+
+    _ne:            (),
+}
+
+impl Enumerator {
+    pub fn new(id: impl Into<Ident>) -> Self {
+        Self { id: id.into(), value: None, evaluated: None, kind: EnumeratorKind::Value, condition: None, variants: Vec::new(), index: 0, follows: None, _ne: () }
+    }
+}
+
+/// What purpose an [`Enumerator`] serves.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EnumeratorKind {
+    /// A regular, meaningful value (e.g. `D3DFMT_A8R8G8B8`)
+    Value,
+
+    /// A `_FORCE_DWORD`/`_FORCE_UINT`-style sentinel that forces the size of the enum (e.g. `D3DFMT_FORCE_DWORD = 0x7fffffff`)
+    ForceSize,
+
+    /// A count or maximum of the other values (e.g. `D3D12_..._COUNT`, `D3DSAMP_..._MAX`, `D3D_NUM_...`)
+    Count,
+
+    /// Another name for a previous enumerator (e.g. `SW_NORMAL` for `SW_SHOWNORMAL`)
+    Alias(Ident),
+}
+
+impl Debug for Enumerator {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(fmt, "Enumerator {{ id: {:?}", self.id)?;
+        if let Some(value) = self.value.as_ref() { write!(fmt, ", value: {:?}", value)?; }
+        if let Some(evaluated) = self.evaluated { write!(fmt, ", evaluated: {}", evaluated)?; }
+        if self.kind != EnumeratorKind::Value { write!(fmt, ", kind: {:?}", self.kind)?; }
+        if let Some(condition) = self.condition.as_ref() { write!(fmt, ", condition: {:?}", condition)?; }
+        if !self.variants.is_empty() { write!(fmt, ", variants: {:?}", self.variants)?; }
+        write!(fmt, ", ... }}")
+    }
+}
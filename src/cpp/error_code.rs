@@ -0,0 +1,153 @@
+use crate::*;
+
+use std::collections::BTreeSet;
+use std::fmt::{self, Debug, Formatter};
+
+
+
+/// `#define E_FAIL _HRESULT_TYPEDEF_(0x80004005L)`, `#define STATUS_WAIT_0 ((NTSTATUS)0x00000000L)`, `#define ERROR_SUCCESS 0L`, etc.
+pub struct ErrorCode {
+    /// Location(s) this error code was defined at.
+    pub defined_at:             BTreeSet<Location>,
+
+    /// Identifier of this error code (e.g. `E_FAIL`)
+    pub id:                     Ident,
+
+    pub kind:                   ErrorCodeKind,
+
+    /// The numeric value of this error code (e.g. `0x80004005`)
+    pub value:                  u32,
+
+    /// The text of any preceding `// MessageText:` comment block (e.g. `Unspecified error`)
+    pub message:                Option<String>,
+
+    pub(crate) _non_exhaustive: (),
+}
+
+/// What kind of error code an [`ErrorCode`] is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ErrorCodeKind {
+    /// `HRESULT` / `SCODE` (e.g. `E_FAIL`)
+    HResult,
+
+    /// `NTSTATUS` (e.g. `STATUS_ACCESS_VIOLATION`)
+    NtStatus,
+
+    /// Win32 error code as returned by `GetLastError()` (e.g. `ERROR_FILE_NOT_FOUND`)
+    Win32,
+}
+
+/// The severity of an [`ErrorCode`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ErrorSeverity {
+    Success,
+    Informational,
+    Warning,
+    Error,
+}
+
+impl ErrorCode {
+    pub fn new(id: impl Into<Ident>, kind: ErrorCodeKind, value: u32) -> Self {
+        Self {
+            defined_at: Default::default(),
+            id: id.into(),
+            kind,
+            value,
+            message: None,
+            _non_exhaustive: Default::default(),
+        }
+    }
+
+    /// `HRESULT`s distinguish only success and error, Win32 error codes are errors unless `0`.
+    pub fn severity(&self) -> ErrorSeverity {
+        match self.kind {
+            ErrorCodeKind::HResult  if self.value & 0x8000_0000 != 0    => ErrorSeverity::Error,
+            ErrorCodeKind::HResult                                      => ErrorSeverity::Success,
+            ErrorCodeKind::NtStatus => match self.value >> 30 {
+                0 => ErrorSeverity::Success,
+                1 => ErrorSeverity::Informational,
+                2 => ErrorSeverity::Warning,
+                _ => ErrorSeverity::Error,
+            },
+            ErrorCodeKind::Win32    if self.value == 0                  => ErrorSeverity::Success,
+            ErrorCodeKind::Win32                                        => ErrorSeverity::Error,
+        }
+    }
+
+    /// The facility (e.g. `FACILITY_WIN32` = `7` for `0x80070005`), or [`None`] for Win32 error codes.
+    pub fn facility(&self) -> Option<u16> {
+        match self.kind {
+            ErrorCodeKind::HResult  => Some(((self.value >> 16) & 0x7FF) as u16),
+            ErrorCodeKind::NtStatus => Some(((self.value >> 16) & 0xFFF) as u16),
+            ErrorCodeKind::Win32    => None,
+        }
+    }
+
+    /// The facility-specific code (e.g. `5` for `0x80070005`), or the entire value for Win32 error codes.
+    pub fn code(&self) -> u32 {
+        match self.kind {
+            ErrorCodeKind::HResult | ErrorCodeKind::NtStatus    => self.value & 0xFFFF,
+            ErrorCodeKind::Win32                                => self.value,
+        }
+    }
+
+    /// Guess the kind of error code `#define id value` is based on `value`.
+    /// Plain numbers are only considered error codes if they had a `// MessageId:` comment block.
+    pub(crate) fn kind_from_define(value: &str, has_message: bool, evaluated: i64) -> Option<ErrorCodeKind> {
+        let idents = value.split(|ch: char| !ch.is_ascii_word_character()).filter(|id| !id.is_empty() && !id.starts_with(|ch: char| ch.is_ascii_digit())).collect::<Vec<_>>();
+        let is_hresult = |id: &str| {
+            ["_HRESULT_TYPEDEF_", "_NDIS_ERROR_TYPEDEF_", "HRESULT", "SCODE", "MAKE_HRESULT", "MAKE_SCODE"].contains(&id)
+            || (id.starts_with("MAKE_") && (id.ends_with("HRESULT") || id.ends_with("STATUS"))) // MAKE_D3DHRESULT, MAKE_DXGI_STATUS, ...
+        };
+
+        if idents.contains(&"NTSTATUS") {
+            Some(ErrorCodeKind::NtStatus)
+        } else if idents.first().is_some_and(|id| is_hresult(id)) {
+            Some(ErrorCodeKind::HResult)
+        } else if !has_message || !idents.is_empty() {
+            None
+        } else if (0 ..= 0xFFFF).contains(&evaluated) {
+            Some(ErrorCodeKind::Win32)
+        } else {
+            Some(ErrorCodeKind::HResult)
+        }
+    }
+}
+
+impl Debug for ErrorCode {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(fmt, "ErrorCode {{ id: {:?}, kind: {:?}, value: 0x{:08X}", self.id, self.kind, self.value)?;
+        if let Some(message) = self.message.as_ref() { write!(fmt, ", message: {:?}", message)?; }
+        write!(fmt, ", .. }}")
+    }
+}
+
+
+
+#[test] fn test_kind_from_define() {
+    use ErrorCodeKind::*;
+    assert_eq!(ErrorCode::kind_from_define("_HRESULT_TYPEDEF_(0x80004005L)",           false, 0), Some(HResult));
+    assert_eq!(ErrorCode::kind_from_define("((HRESULT)0L)",                            false, 0), Some(HResult));
+    assert_eq!(ErrorCode::kind_from_define("MAKE_D3DHRESULT(2154)",                    false, 0), Some(HResult));
+    assert_eq!(ErrorCode::kind_from_define("((NTSTATUS)0xC0000005L)",                  false, 0), Some(NtStatus));
+    assert_eq!(ErrorCode::kind_from_define("2L",                                       true,  2), Some(Win32));
+    assert_eq!(ErrorCode::kind_from_define("2L",                                       false, 2), None);
+    assert_eq!(ErrorCode::kind_from_define("0x80070005",                               true,  0x80070005), Some(HResult));
+    assert_eq!(ErrorCode::kind_from_define("FACILITY_WIN32",                           true,  7), None);
+}
+
+#[test] fn test_severity_facility_code() {
+    let e_accessdenied = ErrorCode::new("E_ACCESSDENIED", ErrorCodeKind::HResult, 0x80070005);
+    assert_eq!(e_accessdenied.severity(), ErrorSeverity::Error);
+    assert_eq!(e_accessdenied.facility(), Some(7));
+    assert_eq!(e_accessdenied.code(),     5);
+
+    let status_buffer_overflow = ErrorCode::new("STATUS_BUFFER_OVERFLOW", ErrorCodeKind::NtStatus, 0x80000005);
+    assert_eq!(status_buffer_overflow.severity(), ErrorSeverity::Warning);
+    assert_eq!(status_buffer_overflow.facility(), Some(0));
+    assert_eq!(status_buffer_overflow.code(),     5);
+
+    let error_success = ErrorCode::new("ERROR_SUCCESS", ErrorCodeKind::Win32, 0);
+    assert_eq!(error_success.severity(), ErrorSeverity::Success);
+    assert_eq!(error_success.facility(), None);
+}
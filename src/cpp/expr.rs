@@ -0,0 +1,334 @@
+use crate::*;
+
+
+
+/// Provides the definitions [`eval_expr`] needs to evaluate identifiers and macros.
+pub(crate) trait ExprContext {
+    /// The value of an object-like `#define ID value`, if any.
+    fn constant(&self, id: &str) -> Option<&str>;
+
+    /// The parameters and body of a function-like `#define ID(params...) body`, if any.
+    fn macro_(&self, id: &str) -> Option<(&[Ident], &str)>;
+
+    /// The value of a non-macro identifier such as an enumerator, if any.
+    fn value(&self, _id: &str) -> Option<i64> { None }
+}
+
+/// Evaluate a C/C++ integer constant expression such as `((HRESULT)0x8000FFFFL)` or `MAKE_HRESULT(1, FACILITY_WIN32, 5)`.
+///
+/// Macros and constants are expanded via `ctx`.
+/// Casts to well known integer types (`DWORD`, `HRESULT`, `unsigned long`, ...) truncate, other casts are ignored.
+/// Returns [`None`] if the expression references anything unknown, or isn't an integer constant expression at all.
+pub(crate) fn eval_expr(expr: &str, ctx: &dyn ExprContext) -> Option<i64> {
+    let tokens = tokenize(expr)?;
+    let tokens = expand(tokens, ctx, 0)?;
+    let mut parser = Parser { tokens: &tokens, pos: 0, ctx };
+    let value = parser.ternary()?;
+    (parser.pos == tokens.len()).then_some(value)
+}
+
+
+
+#[derive(Clone, Debug, PartialEq)]
+enum Tok {
+    Int(i64),
+    Ident(String),
+    Punct(&'static str),
+}
+
+const PUNCTS : &[&str] = &[
+    // N.B. longest first!
+    "<<", ">>", "<=", ">=", "==", "!=", "&&", "||", "##",
+    "(", ")", ",", "?", ":", "+", "-", "*", "/", "%", "<", ">", "&", "|", "^", "~", "!", "#",
+];
+
+fn tokenize(mut s: &str) -> Option<Vec<Tok>> {
+    let mut tokens = Vec::new();
+    loop {
+        s = s.trim_start_cpp_whitespace_comments();
+        let ch = if let Some(ch) = s.chars().next() { ch } else { return Some(tokens) };
+
+        if ch == '\'' || s.starts_with("L'") {
+            let (value, rest) = char_literal(s.trim_start_matches('L'))?;
+            tokens.push(Tok::Int(value));
+            s = rest;
+        } else if ch.is_ascii_digit() {
+            let end = s.find(|ch: char| !(ch.is_ascii_word_character() || ch == '\'')).unwrap_or(s.len());
+            if s[end..].starts_with('.') { return None } // float
+            tokens.push(Tok::Int(s[..end].parse_cpp_int()? as i64));
+            s = &s[end..];
+        } else if ch.is_ascii_word_character() {
+            let end = s.find(|ch: char| !ch.is_ascii_word_character()).unwrap_or(s.len());
+            tokens.push(Tok::Ident(s[..end].into()));
+            s = &s[end..];
+        } else if let Some(p) = PUNCTS.iter().copied().find(|p| s.starts_with(p)) {
+            tokens.push(Tok::Punct(p));
+            s = &s[p.len()..];
+        } else {
+            return None; // strings, floats, `\`, ...
+        }
+    }
+}
+
+/// Parse e.g. `'a'` or `'\0'`, returning the value and the remaining source
+fn char_literal(s: &str) -> Option<(i64, &str)> {
+    let mut chars = s.strip_prefix('\'')?.chars();
+    let value = match chars.next()? {
+        '\\' => match chars.next()? {
+            'n'     => '\n' as i64,
+            'r'     => '\r' as i64,
+            't'     => '\t' as i64,
+            '0'     => 0,
+            'x'     => {
+                let rest = chars.as_str();
+                let end = rest.find(|ch: char| !ch.is_ascii_hexdigit()).unwrap_or(rest.len());
+                let value = i64::from_str_radix(&rest[..end], 16).ok()?;
+                chars = rest[end..].chars();
+                value
+            },
+            other   => other as i64,
+        },
+        '\''    => return None,
+        other   => other as i64,
+    };
+    let rest = chars.as_str().strip_prefix('\'')?;
+    Some((value, rest))
+}
+
+fn expand(tokens: Vec<Tok>, ctx: &dyn ExprContext, depth: u32) -> Option<Vec<Tok>> {
+    if depth > 32 { return None } // probably recursive
+    if !tokens.iter().any(|t| matches!(t, Tok::Ident(_))) { return Some(tokens) }
+
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let id = match &tokens[i] {
+            Tok::Ident(id) if id != "defined" => id,
+            other => { out.push(other.clone()); i += 1; continue },
+        };
+
+        if tokens.get(i+1) == Some(&Tok::Punct("(")) {
+            if let Some((params, body)) = ctx.macro_(id) {
+                // parse arguments of `ID(arg, arg, ...)`
+                let mut args = vec![Vec::new()];
+                let mut parens = 0;
+                i += 2;
+                loop {
+                    let token = tokens.get(i)?;
+                    i += 1;
+                    match token {
+                        Tok::Punct("(")                 => parens += 1,
+                        Tok::Punct(")") if parens == 0  => break,
+                        Tok::Punct(")")                 => parens -= 1,
+                        Tok::Punct(",") if parens == 0  => { args.push(Vec::new()); continue },
+                        _                               => {},
+                    }
+                    args.last_mut().unwrap().push(token.clone());
+                }
+                if params.is_empty() && args.len() == 1 && args[0].is_empty() { args.clear() }
+                if args.len() != params.len() { return None }
+
+                let mut body_tokens = Vec::new();
+                for token in tokenize(body)?.into_iter() {
+                    match &token {
+                        Tok::Ident(id) => match params.iter().position(|p| p.as_str() == id) {
+                            Some(p) => body_tokens.extend(args[p].iter().cloned()),
+                            None    => body_tokens.push(token),
+                        },
+                        Tok::Punct("#") | Tok::Punct("##") => return None, // stringizing / token pasting not supported
+                        _ => body_tokens.push(token),
+                    }
+                }
+                out.extend(expand(body_tokens, ctx, depth+1)?);
+                continue;
+            }
+        }
+
+        if let Some(value) = ctx.constant(id) {
+            out.extend(expand(tokenize(value)?, ctx, depth+1)?);
+        } else {
+            out.push(tokens[i].clone());
+        }
+        i += 1;
+    }
+    Some(out)
+}
+
+struct Parser<'t> {
+    tokens: &'t [Tok],
+    pos:    usize,
+    ctx:    &'t dyn ExprContext,
+}
+
+impl<'t> Parser<'t> {
+    fn peek(&self) -> Option<&Tok> { self.tokens.get(self.pos) }
+    fn peek_punct(&self) -> Option<&'static str> { match self.peek() { Some(Tok::Punct(p)) => Some(p), _ => None } }
+    fn next(&mut self) -> Option<&'t Tok> { let t = self.tokens.get(self.pos); self.pos += 1; t }
+    fn expect(&mut self, p: &'static str) -> Option<()> { (self.next()? == &Tok::Punct(p)).then_some(()) }
+
+    fn ternary(&mut self) -> Option<i64> {
+        let cond = self.binary(0)?;
+        if self.peek_punct() != Some("?") { return Some(cond) }
+        self.pos += 1;
+        let a = self.ternary()?;
+        self.expect(":")?;
+        let b = self.ternary()?;
+        Some(if cond != 0 { a } else { b })
+    }
+
+    fn binary(&mut self, min_prec: u32) -> Option<i64> {
+        let mut lhs = self.unary()?;
+        while let Some(op) = self.peek_punct() {
+            let prec = match op {
+                "||"                    => 1,
+                "&&"                    => 2,
+                "|"                     => 3,
+                "^"                     => 4,
+                "&"                     => 5,
+                "==" | "!="             => 6,
+                "<" | "<=" | ">" | ">=" => 7,
+                "<<" | ">>"             => 8,
+                "+" | "-"               => 9,
+                "*" | "/" | "%"         => 10,
+                _                       => break,
+            };
+            if prec < min_prec { break }
+            self.pos += 1;
+            let rhs = self.binary(prec + 1)?;
+            lhs = match op {
+                "||"    => ((lhs != 0) || (rhs != 0)) as i64,
+                "&&"    => ((lhs != 0) && (rhs != 0)) as i64,
+                "|"     => lhs | rhs,
+                "^"     => lhs ^ rhs,
+                "&"     => lhs & rhs,
+                "=="    => (lhs == rhs) as i64,
+                "!="    => (lhs != rhs) as i64,
+                "<"     => (lhs <  rhs) as i64,
+                "<="    => (lhs <= rhs) as i64,
+                ">"     => (lhs >  rhs) as i64,
+                ">="    => (lhs >= rhs) as i64,
+                "<<"    => lhs.checked_shl(u32::try_from(rhs).ok()?)?,
+                ">>"    => lhs.checked_shr(u32::try_from(rhs).ok()?)?,
+                "+"     => lhs.wrapping_add(rhs),
+                "-"     => lhs.wrapping_sub(rhs),
+                "*"     => lhs.wrapping_mul(rhs),
+                "/"     => lhs.checked_div(rhs)?,
+                "%"     => lhs.checked_rem(rhs)?,
+                _       => unreachable!(),
+            };
+        }
+        Some(lhs)
+    }
+
+    fn unary(&mut self) -> Option<i64> {
+        match self.peek_punct() {
+            Some("-") => { self.pos += 1; Some(self.unary()?.wrapping_neg()) },
+            Some("+") => { self.pos += 1; self.unary() },
+            Some("~") => { self.pos += 1; Some(!self.unary()?) },
+            Some("!") => { self.pos += 1; Some((self.unary()? == 0) as i64) },
+            Some("(") => {
+                if let Some((cast, end)) = self.cast() {
+                    self.pos = end;
+                    let value = self.unary()?;
+                    return Some(cast.map_or(value, |cast| cast(value)));
+                }
+                self.pos += 1;
+                let value = self.ternary()?;
+                self.expect(")")?;
+                Some(value)
+            },
+            _ => match self.next()? {
+                Tok::Int(i)     => Some(*i),
+                Tok::Ident(id)  => self.ctx.value(id),
+                Tok::Punct(_)   => None,
+            },
+        }
+    }
+
+    /// Check for e.g. `(DWORD)` or `(unsigned long)` at the current position.
+    /// Returns the truncating conversion (if any), and the position following the closing `)`.
+    #[allow(clippy::type_complexity)]
+    fn cast(&self) -> Option<(Option<fn(i64) -> i64>, usize)> {
+        let mut ty = Vec::new();
+        let mut end = self.pos + 1;
+        loop {
+            match self.tokens.get(end)? {
+                Tok::Ident(id) if self.ctx.value(id).is_none()  => ty.push(id.as_str()),
+                Tok::Punct("*")                                 => ty.push("*"),
+                Tok::Punct(")") if !ty.is_empty()               => break,
+                _                                               => return None,
+            }
+            end += 1;
+        }
+        end += 1;
+        match self.tokens.get(end)? {
+            Tok::Int(_) | Tok::Ident(_) => {},
+            Tok::Punct(p) if "( ~ ! - +".split(' ').any(|q| q == *p) => {},
+            _ => return None,
+        }
+
+        let cast : Option<fn(i64) -> i64> = match ty.join(" ").as_str() {
+            "BYTE" | "UCHAR" | "UINT8" | "unsigned char"                                                    => Some(|v| v as u8  as i64),
+            "CHAR" | "INT8" | "char" | "signed char"                                                        => Some(|v| v as i8  as i64),
+            "WORD" | "USHORT" | "UINT16" | "unsigned short"                                                 => Some(|v| v as u16 as i64),
+            "SHORT" | "INT16" | "short"                                                                     => Some(|v| v as i16 as i64),
+            "DWORD" | "UINT" | "ULONG" | "UINT32" | "D3DCOLOR" | "unsigned" | "unsigned int" | "unsigned long" => Some(|v| v as u32 as i64),
+            "INT" | "LONG" | "INT32" | "BOOL" | "HRESULT" | "NTSTATUS" | "SCODE" | "int" | "long"          => Some(|v| v as i32 as i64),
+            _                                                                                               => None,
+        };
+        Some((cast, end))
+    }
+}
+
+
+
+#[test] fn test_eval_expr() {
+    struct Ctx(std::collections::HashMap<&'static str, (Vec<Ident>, &'static str)>);
+    impl ExprContext for Ctx {
+        fn constant(&self, id: &str) -> Option<&str> {
+            match id {
+                "SEVERITY_ERROR"    => Some("1"),
+                "FACILITY_WIN32"    => Some("7"),
+                "E_FAIL"            => Some("_HRESULT_TYPEDEF_(0x80004005L)"),
+                "RECURSIVE"         => Some("RECURSIVE"),
+                _                   => None,
+            }
+        }
+        fn macro_(&self, id: &str) -> Option<(&[Ident], &str)> { self.0.get(id).map(|(params, body)| (&params[..], *body)) }
+        fn value(&self, id: &str) -> Option<i64> { (id == "ENUMERATOR").then_some(3) }
+    }
+
+    let params = |p: &'static str| p.split(',').map(Ident::from).collect::<Vec<_>>();
+    let ctx = Ctx([
+        ("_HRESULT_TYPEDEF_",   (params("_sc"),                 "((HRESULT)_sc)")),
+        ("MAKE_HRESULT",        (params("sev,fac,code"),        "((HRESULT) (((unsigned long)(sev)<<31) | ((unsigned long)(fac)<<16) | ((unsigned long)(code))) )")),
+        ("MAKEFOURCC",          (params("ch0,ch1,ch2,ch3"),     "((DWORD)(BYTE)(ch0) | ((DWORD)(BYTE)(ch1) << 8) | ((DWORD)(BYTE)(ch2) << 16) | ((DWORD)(BYTE)(ch3) << 24 ))")),
+        ("STRINGIZE",           (params("x"),                   "#x")),
+    ].into_iter().collect());
+
+    let eval = |expr| eval_expr(expr, &ctx);
+    assert_eq!(eval("1"),                                           Some(1));
+    assert_eq!(eval("(1 << 3) | 0x1"),                              Some(9));
+    assert_eq!(eval("1 + 2 * 3"),                                   Some(7));
+    assert_eq!(eval("(1 + 2) * 3"),                                 Some(9));
+    assert_eq!(eval("10 - 3 - 2"),                                  Some(5));
+    assert_eq!(eval("1 ? 2 : 3"),                                   Some(2));
+    assert_eq!(eval("~0"),                                          Some(-1));
+    assert_eq!(eval("(UINT)~0"),                                    Some(0xFFFFFFFF));
+    assert_eq!(eval("(UINT)-1"),                                    Some(0xFFFFFFFF));
+    assert_eq!(eval("((NTSTATUS)0xC0000001L)"),                     Some(0xC0000001u32 as i32 as i64));
+    assert_eq!(eval("ENUMERATOR + 1"),                              Some(4));
+    assert_eq!(eval("(ENUMERATOR) - 1"),                            Some(2));
+    assert_eq!(eval("E_FAIL"),                                      Some(0x80004005u32 as i32 as i64));
+    assert_eq!(eval("MAKE_HRESULT(SEVERITY_ERROR, FACILITY_WIN32, 5)"), Some(0x80070005u32 as i32 as i64));
+    assert_eq!(eval("MAKEFOURCC('D', 'X', 'T', '1')"),              Some(0x31545844));
+    assert_eq!(eval("'\\0'"),                                       Some(0));
+    assert_eq!(eval("UNKNOWN"),                                     None);
+    assert_eq!(eval("RECURSIVE"),                                   None);
+    assert_eq!(eval("STRINGIZE(1)"),                                None);
+    assert_eq!(eval("1.0f"),                                        None);
+    assert_eq!(eval("\"string\""),                                  None);
+    assert_eq!(eval("1 / 0"),                                       None);
+    assert_eq!(eval("(1"),                                          None);
+    assert_eq!(eval("1 2"),                                         None);
+}
@@ -7,30 +7,139 @@ use std::num::NonZeroU32;
 
 /// `ty id;` as found inside a `struct` or `union`.
 pub struct Field {
-    pub ty:     Type,
-    pub id:     Ident,
-    pub bits:   Option<NonZeroU32>,
-    _ne:        (),
+    pub ty:         Type,
+    pub id:         Ident,
+
+    /// Array dimensions, as written (e.g. `["3", "4"]` for `FLOAT Transform[3][4];`)
+    pub array:      Vec<String>,
+
+    /// The evaluated width of a bitfield (e.g. `3` for `UINT Foo : D3D12_SHADER_COMPONENT_MAPPING_SHIFT;`)
+    pub bits:       Option<NonZeroU32>,
+
+    /// The width of a bitfield, as written (e.g. `D3D12_SHADER_COMPONENT_MAPPING_SHIFT`)
+    pub bits_expr:  Option<String>,
+
+    /// Where this bitfield was placed, per MSVC's bitfield layout rules.
+    pub bit_layout: Option<BitfieldLayout>,
+
+    /// The `DECLSPEC_ALIGN(n)` / `__declspec(align(n))` of this field, if any.
+    pub align:      Option<u32>,
+
+    /// The preprocessor condition this field was declared under, relative to the enclosing `struct { ... }` (e.g. `(DIRECT3D_VERSION >= 0x0900)`)
+    pub condition:  Option<String>,
+
+    /// Other declarations of this field under different preprocessor conditions (e.g. the `#else` branch), in declaration order.
+    pub variants:   Vec<Field>,
+
+    /// Location this field was declared at, if known.
+    pub defined_at: Option<Location>,
+
+    /// `true` if this field has no name in C (e.g. `union { ... };` or `UINT : 4;`), in which case [`Field::id`] is synthetic (e.g. `_anonymous_union_0`.)
+    /// Members of anonymous `struct`s and `union`s are accessed as if they were members of the containing aggregate.
+    pub anonymous:  bool,
+
+    /// The `DUMMYUNIONNAME` / `DUMMYSTRUCTNAME` style macro naming an otherwise anonymous member, if any.
+    /// These expand to `u` / `s` (etc.) when `NONAMELESSUNION` is defined, and to nothing otherwise.
+    pub dummy_name: Option<Ident>,
+
+    _ne:            (),
+}
+
+/// Where a bitfield [`Field`] lives within its containing `struct` or `union`.
+///
+/// MSVC packs consecutive bitfields into the same storage unit only while their declared types have the same size and the bits still fit.
+/// Any non-bitfield member, or a zero-width bitfield, ends the current storage unit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BitfieldLayout {
+    /// Index of the storage unit within the containing aggregate (bitfields sharing a storage unit share an index.)
+    pub storage_unit:   u32,
+
+    /// The size of the storage unit in bytes (e.g. `4` for `UINT`)
+    pub storage_size:   u32,
+
+    /// The offset of the first bit of this bitfield within the storage unit, counting from the least significant bit.
+    pub bit_offset:     u32,
 }
 
 impl Field {
     pub fn new(ty: impl Into<Ident>, id: impl Into<Ident>) -> Self {
-        Self { ty: Type::Basic(ty.into()), id: id.into(), bits: Default::default(), _ne: () }
+        Self::new_type(Type::Basic(ty.into()), id)
     }
 
     pub fn new_agg(ty: impl Into<AggregateData>, id: impl Into<Ident>) -> Self {
-        Self { ty: Type::Aggregate(ty.into()), id: id.into(), bits: Default::default(), _ne: () }
+        Self::new_type(Type::Aggregate(ty.into()), id)
     }
 
     pub fn new_enum(ty: impl Into<EnumData>, id: impl Into<Ident>) -> Self {
-        Self { ty: Type::Enum(ty.into()), id: id.into(), bits: Default::default(), _ne: () }
+        Self::new_type(Type::Enum(ty.into()), id)
+    }
+
+    /// Create an unnamed field of type `ty` (e.g. `DECLSPEC_ALIGN ( 16 ) M128A`), moving any `DECLSPEC_ALIGN` / `__declspec` out of the type and into [`Field::align`].
+    pub(crate) fn new_declspec(ty: &str) -> Self {
+        let mut tokens = Vec::new();
+        let mut align = None;
+        let mut rest = ty.split_ascii_whitespace().peekable();
+        while let Some(token) = rest.next() {
+            if !["DECLSPEC_ALIGN", "__declspec"].contains(&token) || rest.peek() != Some(&"(") {
+                tokens.push(token);
+                continue
+            }
+            let mut group = vec![token];
+            let mut depth = 0;
+            for token in rest.by_ref() {
+                group.push(token);
+                match token { "(" => depth += 1, ")" => depth -= 1, _ => {} }
+                if depth == 0 { break }
+            }
+            if let Some(a) = declspec_align(group.into_iter()) { align = Some(a); }
+        }
+        let mut field = Self::new(tokens.join(" "), "");
+        field.align = align;
+        field
+    }
+
+    fn new_type(ty: Type, id: impl Into<Ident>) -> Self {
+        Self { ty, id: id.into(), array: Vec::new(), bits: None, bits_expr: None, bit_layout: None, align: None, condition: None, variants: Vec::new(), defined_at: None, anonymous: false, dummy_name: None, _ne: () }
+    }
+}
+
+impl Field {
+    /// Describe this field's declaration for comparison purposes, e.g. `DWORD Flags : 8` or `FLOAT Transform[3][4]`.
+    /// Inline aggregates and enums are described only by their category.
+    pub(crate) fn describe(&self) -> String {
+        let mut s = match &self.ty {
+            Type::Basic(ty)         => format!("{} {}", ty, self.id),
+            Type::Aggregate(agg)    => format!("{} {{ ... }} {}", agg.category, self.id),
+            Type::Enum(_)           => format!("enum {{ ... }} {}", self.id),
+        };
+        for dim in self.array.iter() { s.push_str(&format!("[{}]", dim)); }
+        if let Some(bits) = self.bits_expr.as_ref() { s.push_str(&format!(" : {}", bits)); }
+        s
     }
 }
 
 impl Debug for Field {
     fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
         write!(fmt, "Field {{ ty: {:?}, id: {:?}", self.ty, self.id)?;
+        if !self.array.is_empty() { write!(fmt, ", array: {:?}", self.array)?; }
+        if self.anonymous { write!(fmt, ", anonymous: true")?; }
+        if let Some(dummy_name) = self.dummy_name.as_ref() { write!(fmt, ", dummy_name: {:?}", dummy_name)?; }
         if let Some(bits) = self.bits { write!(fmt, ", bits: {}", bits)?; }
+        else if let Some(bits_expr) = self.bits_expr.as_ref() { write!(fmt, ", bits_expr: {:?}", bits_expr)?; }
+        if let Some(align) = self.align { write!(fmt, ", align: {}", align)?; }
+        if let Some(condition) = self.condition.as_ref() { write!(fmt, ", condition: {:?}", condition)?; }
+        if !self.variants.is_empty() { write!(fmt, ", variants: {:?}", self.variants)?; }
+        if let Some(l) = self.bit_layout { write!(fmt, ", storage_unit: {}, bit_offset: {}", l.storage_unit, l.bit_offset)?; }
         write!(fmt, ", ... }}")
     }
 }
+
+/// Parse the alignment of `DECLSPEC_ALIGN ( n )` or `__declspec ( align ( n ) )` tokens.
+pub(crate) fn declspec_align<'t>(mut tokens: impl Iterator<Item = &'t str>) -> Option<u32> {
+    let n = match tokens.next()? {
+        "DECLSPEC_ALIGN"    => { if tokens.next()? != "(" { return None } tokens.next()? },
+        "__declspec"        => { if tokens.next()? != "(" || tokens.next()? != "align" || tokens.next()? != "(" { return None } tokens.next()? },
+        _                   => return None,
+    };
+    u32::try_from(n.parse_cpp_int()?).ok()
+}
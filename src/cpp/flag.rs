@@ -1,10 +1,57 @@
 use crate::*;
 
+use std::collections::BTreeSet;
 
 
-/// `typedef enum { ... } id;`
+
+/// `typedef enum { ... } id;` used as a set of bit flags, e.g. `DEFINE_ENUM_FLAG_OPERATORS(id)`.
 #[derive(Debug)]
 pub struct Flags {
     pub id:                     Ident,
+
+    /// The underlying [`Enum`] (typically the same as `id`.)
+    pub enum_:                  Ident,
+
+    /// Location(s) of `DEFINE_ENUM_FLAG_OPERATORS(id)`, if any.
+    /// If empty, this enum was merely guessed to be a set of flags based on it's values.
+    pub operators_at:           BTreeSet<Location>,
+
+    /// Single bit enumerators (e.g. `D3D12_RESOURCE_FLAG_ALLOW_RENDER_TARGET = 0x1`)
+    pub bits:                   Vec<(Ident, u64)>,
+
+    /// Zero or multi-bit enumerators (e.g. `D3D12_RESOURCE_FLAG_NONE = 0` or `..._ALL = A | B`)
+    pub combinations:           Vec<(Ident, u64)>,
+
     pub(crate) _non_exhaustive: (),
 }
+
+impl Flags {
+    /// Build flags from an [`Enum`] (ignoring [`EnumeratorKind::ForceSize`] sentinels), or [`None`] if not all enumerators could be evaluated.
+    pub(crate) fn from_enum(id: Ident, e: &Enum) -> Option<Self> {
+        let mut bits = Vec::new();
+        let mut combinations = Vec::new();
+        for enumerator in e.values.values_by_insert().filter(|v| v.kind != EnumeratorKind::ForceSize) {
+            let value = enumerator.evaluated?;
+            let value = if value < 0 { value as u32 as u64 } else { value as u64 };
+            if value.count_ones() == 1 {
+                bits.push((enumerator.id.clone(), value));
+            } else {
+                combinations.push((enumerator.id.clone(), value));
+            }
+        }
+        Some(Self { id, enum_: e.id.clone(), operators_at: Default::default(), bits, combinations, _non_exhaustive: () })
+    }
+}
+
+/// Guess if `values` are bit flags: at least two distinct single bits, every value made of those bits, and not merely sequential.
+pub(crate) fn looks_like_flags(values: impl Iterator<Item = i64>) -> bool {
+    let mut values = values.filter(|v| *v != 0).collect::<Vec<_>>();
+    values.sort_unstable();
+    values.dedup();
+
+    let bits = values.iter().copied().filter(|v| *v > 0 && v.count_ones() == 1).fold(0, |a, b| a | b);
+    let all_bits = values.iter().all(|v| *v & !bits == 0);
+    let sequential = values.len() >= 2 && values.windows(2).all(|w| w[0] + 1 == w[1]);
+
+    bits.count_ones() >= 2 && all_bits && !sequential
+}
@@ -0,0 +1,86 @@
+use crate::*;
+
+use std::fmt::{self, Debug, Display, Formatter};
+
+
+
+/// A `GUID`, `IID`, or `CLSID` such as `00000000-0000-0000-C000-000000000046`
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Guid {
+    pub data1:  u32,
+    pub data2:  u16,
+    pub data3:  u16,
+    pub data4:  [u8; 8],
+}
+
+impl Guid {
+    /// Parse e.g. `00000000-0000-0000-C000-000000000046` (surrounding `"quotes"` and `{braces}` are optional)
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim_matches('"');
+        let s = s.strip_prefix('{').and_then(|s| s.strip_suffix('}')).unwrap_or(s);
+
+        let mut parts = s.split('-');
+        let data1   = parts.next().filter(|p| p.len() ==  8)?;
+        let data2   = parts.next().filter(|p| p.len() ==  4)?;
+        let data3   = parts.next().filter(|p| p.len() ==  4)?;
+        let data4a  = parts.next().filter(|p| p.len() ==  4)?;
+        let data4b  = parts.next().filter(|p| p.len() == 12)?;
+        if parts.next().is_some() { return None }
+
+        let data4 = format!("{}{}", data4a, data4b);
+        let mut bytes = [0u8; 8];
+        for (i, b) in bytes.iter_mut().enumerate() {
+            *b = u8::from_str_radix(data4.get(2*i..2*i+2)?, 16).ok()?;
+        }
+
+        Some(Self {
+            data1: u32::from_str_radix(data1, 16).ok()?,
+            data2: u16::from_str_radix(data2, 16).ok()?,
+            data3: u16::from_str_radix(data3, 16).ok()?,
+            data4: bytes,
+        })
+    }
+
+    /// Parse the 11 numeric arguments of e.g. `DEFINE_GUID(IID_Foo, 0x81bdcbca, 0x64d4, 0x426d, 0xae, 0x8d, 0xad, 0x1, 0x47, 0xf4, 0x27, 0x5c)`
+    pub(crate) fn from_define_guid_args(args: &[&str]) -> Option<Self> {
+        if args.len() != 11 { return None }
+        let mut data4 = [0u8; 8];
+        for (b, arg) in data4.iter_mut().zip(args[3..].iter()) {
+            *b = u8::try_from(arg.parse_cpp_int()?).ok()?;
+        }
+        Some(Self {
+            data1: u32::try_from(args[0].parse_cpp_int()?).ok()?,
+            data2: u16::try_from(args[1].parse_cpp_int()?).ok()?,
+            data3: u16::try_from(args[2].parse_cpp_int()?).ok()?,
+            data4,
+        })
+    }
+}
+
+impl Debug for Guid {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(fmt, "\"{}\"", self)
+    }
+}
+
+impl Display for Guid {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        let d = &self.data4;
+        write!(fmt, "{:08X}-{:04X}-{:04X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}", self.data1, self.data2, self.data3, d[0], d[1], d[2], d[3], d[4], d[5], d[6], d[7])
+    }
+}
+
+
+
+#[test] fn test_parse() {
+    let iunknown = Guid { data1: 0, data2: 0, data3: 0, data4: [0xC0, 0, 0, 0, 0, 0, 0, 0x46] };
+    assert_eq!(Some(iunknown), Guid::parse("00000000-0000-0000-C000-000000000046"));
+    assert_eq!(Some(iunknown), Guid::parse("\"00000000-0000-0000-C000-000000000046\""));
+    assert_eq!(Some(iunknown), Guid::parse("{00000000-0000-0000-c000-000000000046}"));
+    assert_eq!(None, Guid::parse("00000000-0000-0000-C000-00000000004"));
+    assert_eq!(None, Guid::parse("00000000-0000-0000-C000-000000000046-0"));
+    assert_eq!(iunknown.to_string(), "00000000-0000-0000-C000-000000000046");
+
+    let args = "0x81bdcbca, 0x64d4, 0x426d, 0xae, 0x8d, 0xad, 0x1, 0x47, 0xf4, 0x27, 0x5c".split(", ").collect::<Vec<_>>();
+    assert_eq!(Guid::from_define_guid_args(&args), Guid::parse("81BDCBCA-64D4-426D-AE8D-AD0147F4275C"));
+}
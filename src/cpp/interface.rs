@@ -19,19 +19,61 @@ pub struct Interface {
     /// The base class/interface, if any, such as `IUnknown`.
     pub base:                   Option<Ident>,
 
-    /// The methods belonging to this interface.
+    /// The interface ID, if known, such as `00000000-0000-0000-C000-000000000046`.
+    /// Sourced from `MIDL_INTERFACE("...")`, `DECLSPEC_UUID("...")`, `__declspec(uuid("..."))`, or `DEFINE_GUID(IID_..., ...)`.
+    pub iid:                    Option<Guid>,
+
+    /// The `IID_*` constant declared for this interface, if any, such as `IID_IUnknown`.
+    pub iid_constant:           Option<Ident>,
+
+    /// The methods belonging to this interface, in declaration order.
     /// May include methods inherited from `base` if not cleaned up.
-    pub(crate) all_methods:     VecMap<Ident, Method>,
+    pub(crate) all_methods:     Vec<Method>,
+
+    /// C macros wrapping this interface's methods (including inherited ones), such as `IDirect3D9_AddRef`.
+    /// Populated during cleanup, which removes them from [`Root::macros`].
+    pub macro_wrappers:         Vec<MethodWrapper>,
+
+    /// What kind of interface this is, based on what it ultimately derives from.
+    /// Computed during cleanup ([`InterfaceKind::UnknownBase`] until then.)
+    pub kind:                   InterfaceKind,
+
+    /// Problems found with this interface, such as disagreements with it's C `IFooVtbl`.
+    pub issues:                 Vec<Issue>,
 
     pub(crate) _non_exhaustive: (),
 }
 
+/// What an [`Interface`] ultimately derives from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum InterfaceKind {
+    /// `IUnknown` or an interface deriving from it:  reference counted via `AddRef` / `Release`.
+    Com,
+
+    /// `IInspectable` or an interface deriving from it:  a WinRT interface, which is also COM.
+    WinRt,
+
+    /// A plain C++ vtable without `IUnknown` methods, such as `IXAudio2VoiceCallback`.
+    Vtable,
+
+    /// Derives from an interface that wasn't found (or has cyclic bases), so it's unclear if this is COM or not.
+    UnknownBase,
+}
+
+impl InterfaceKind {
+    /// Does this interface have `QueryInterface` / `AddRef` / `Release`?
+    pub fn is_com(self) -> bool { matches!(self, InterfaceKind::Com | InterfaceKind::WinRt) }
+}
+
 impl Debug for Interface {
     fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
         fmt.debug_struct("Interface")
             .field("id",            &self.id                        )
             .field("base",          &self.base                      )
+            .field("kind",          &self.kind                      )
+            .field("iid",           &self.iid                       )
             .field("methods",       &self.methods().map(|m| m.f.id.as_str()).collect::<Vec<_>>())
+            .field("issues",        &self.issues                    )
             .finish_non_exhaustive()
     }
 }
@@ -45,76 +87,467 @@ impl Interface {
         Self {
             defined_at: Default::default(),
             id, base,
+            iid: None,
+            iid_constant: None,
             all_methods: Default::default(),
+            macro_wrappers: Vec::new(),
+            kind: InterfaceKind::UnknownBase,
+            issues: Vec::new(),
             _non_exhaustive: ()
         }
     }
 
-    pub fn methods(&self) -> impl Iterator<Item = &Method> { self.all_methods.values_by_key().filter(|m| !m.is_inherited()) }
+    /// The methods declared by this interface (excluding inherited ones), sorted by name.  Overloads are sorted by declaration order.
+    pub fn methods(&self) -> impl Iterator<Item = &Method> {
+        let mut methods = self.all_methods.iter().filter(|m| !m.is_inherited()).collect::<Vec<_>>();
+        methods.sort_by(|a, b| a.f.id.cmp(&b.f.id));
+        methods.into_iter()
+    }
 
+    /// The first method named `name` declared by this interface, if any.
     pub fn method(&self, name: impl AsRef<str>) -> Option<&Method> {
-        let m = self.all_methods.get(name.as_ref())?;
-        (!m.is_inherited()).then(|| m)
+        self.overloads(name).next()
     }
 
-    pub(crate) fn add_from_cpp(&mut self, interface_start: &Location, src: &mut SrcReader) -> io::Result<()> {
-        while let Some(SrcLine { location, trimmed, .. }) = src.next_line() {
-            if trimmed == "" { continue }
-            if trimmed == "{" { break }
-            return Err(err_expected(&location, "`{` after `*_INTERFACE(...)` macro"));
+    /// All methods named `name` declared by this interface, in declaration order.
+    pub fn overloads(&self, name: impl AsRef<str>) -> impl Iterator<Item = &Method> {
+        let name = Ident::own(name.as_ref());
+        self.all_methods.iter().filter(move |m| m.f.id == name && !m.is_inherited())
+    }
+
+    /// Compare a redefinition of this interface in vtable order, so reordered methods are reported as well as added or missing ones.
+    pub(crate) fn differences(&self, other: &Interface) -> Vec<String> {
+        let mut diffs = Vec::new();
+        if self.base != other.base { diffs.push(format!("base {:?} vs {:?}", self.base, other.base)); }
+        if let (Some(a), Some(b)) = (self.iid, other.iid) {
+            if a != b { diffs.push(format!("IID {} vs {}", a, b)); }
         }
 
-        while let Some(SrcLine { location, trimmed, .. }) = src.next_line() {
-            if trimmed == "}" { return Ok(()) }
-            if trimmed == "};" { return Ok(()) }
-
-            // e.g.:        `virtual HRESULT STDMETHODCALLTYPE GetFormat( `
-            // but exclude: `HRESULT ( STDMETHODCALLTYPE *GetFormat )(`
-            if let Some(method) = trimmed.split_once_trim(" STDMETHODCALLTYPE ")   // find abi marker
-                .filter(|(_, after)| !after.starts_with("*"))                   // exclude function pointers
-                .and_then(|(_, after)| after.split_once_trim("("))              // start of arguments list
-                .map(|(before, _params)| before.try_rsplit_once_trim(" "))      // find start of method name
-                .map(|(_prev, method)| method)
-            {
-                if Method::valid_name(method) {
-                    let method = Method::new(self.id.clone(), Ident::own(method));
-                    self.add_method(&location, method);
-                }
-                continue
+        let a = msvc_vtable_order(&self.all_methods);
+        let b = msvc_vtable_order(&other.all_methods);
+        let mut sorted_a = a.iter().map(|m| m.f.id.as_str()).collect::<Vec<_>>();
+        let mut sorted_b = b.iter().map(|m| m.f.id.as_str()).collect::<Vec<_>>();
+        sorted_a.sort_unstable();
+        sorted_b.sort_unstable();
+        let reordered = if sorted_a == sorted_b { " (same methods in a different order)" } else { "" };
+
+        for i in 0 .. a.len().max(b.len()) {
+            match (a.get(i), b.get(i)) {
+                (Some(a), Some(b)) => {
+                    let (da, db) = (format!("{}{}", a.f.id, a.signature()), format!("{}{}", b.f.id, b.signature()));
+                    if da != db { diffs.push(format!("method {} is `{}` vs `{}`{}", i, da, db, if a.f.id != b.f.id { reordered } else { "" })); }
+                },
+                (Some(a), None) => diffs.push(format!("method `{}` missing from redefinition", a.f.id)),
+                (None, Some(b)) => diffs.push(format!("method `{}` only in redefinition", b.f.id)),
+                (None, None)    => {},
             }
+        }
+        diffs
+    }
 
-            // STDMETHOD(QueryInterface)(
-            if let Some(method) = trimmed.split_once_trim("STDMETHOD(")
-                .and_then(|(_, after)| after.split_once_trim(")"))
-                .map(|(method, _)| method)
-            {
-                if Method::valid_name(method) {
-                    let method = Method::new(self.id.clone(), Ident::own(method));
-                    self.add_method(&location, method);
-                }
+    /// Parse the body of an interface, given everything up to and including the opening `{` has already been parsed.
+    pub(crate) fn add_from_cpp(&mut self, interface_start: &Location, src: &mut SrcReader) -> io::Result<()> {
+        let mut conditions = Conditions::default();
+        let mut depth = 0; // of `{ ... }`s nested inside the interface, such as the bodies of d2d1.h's inline overloads
+        while let Some(SrcLine { location, trimmed, .. }) = src.next_line() {
+            let trimmed = trimmed.without_cpp_comments();
+            let trimmed = trimmed.trim();
+            let braces = |s: &str| s.matches('{').count() as isize - s.matches('}').count() as isize;
+            if depth > 0 { depth += braces(trimmed); continue }
+            if trimmed.starts_with('}') { return Ok(()) } // e.g. `};`, or `} IFoo;` for `typedef interface IFoo { ... } IFoo;`
+            if let Some(pp) = trimmed.strip_prefix('#') {
+                if let Err(err) = conditions.directive(pp) { warning!(at: &location.path, line: location.line_no_or_0(), column: location.col_no_or_0(), "{}", err); }
                 continue
             }
 
-            // STDMETHOD_(ULONG, AddRef)(
-            if let Some((_ret, method)) = trimmed.split_once_trim("STDMETHOD_(")
-                .and_then(|(_, after)| after.split_once_trim(")"))
-                .and_then(|(ret_method, _)| ret_method.split_once_trim(","))
-            {
-                if Method::valid_name(method) {
-                    let method = Method::new(self.id.clone(), Ident::own(method));
-                    self.add_method(&location, method);
-                }
-                continue
+            // e.g. `DECLARE_INTERFACE(IFoo) { STDMETHOD(Foo)(THIS) PURE; STDMETHOD(Bar)(THIS) PURE; };`
+            let mut rest = trimmed.to_string();
+            while let Some(more) = self.add_method_from_line(&location, &rest, &conditions, src) {
+                rest = more.trim().to_string();
+                if rest.starts_with('}') { return Ok(()) }
             }
+            depth = braces(&rest);
+            if depth < 0 { return Ok(()) } // e.g. `CONST_VTBL struct IFooVtbl *lpVtbl; };`
         }
 
         Err(unexpected_eof(&interface_start, &format!("closing `}}` for interface `{}`", self.id)))
     }
 
+    /// Parse a method declaration starting on `trimmed`, if any, reading more lines from `src` if the parameter list continues.
+    ///
+    /// Returns the rest of the (last) line following the declaration's `;`, which may contain more declarations, or [`None`] if no method was parsed.
+    fn add_method_from_line(&mut self, location: &Location, line: &str, conditions: &Conditions, src: &mut SrcReader) -> Option<String> {
+        let trimmed = line.split(';').next().unwrap_or(""); // only the first declaration
+        let mut method = None;
+
+        // e.g.:        `virtual HRESULT STDMETHODCALLTYPE GetFormat( `
+        // or:          `virtual D3D12_HEAP_DESC *STDMETHODCALLTYPE GetDesc( `
+        // but exclude: `HRESULT ( STDMETHODCALLTYPE *GetFormat )(`
+        if let Some((ret, method_params)) = trimmed.find_token("STDMETHODCALLTYPE")        // find abi marker
+            .map(|i| (trimmed[..i].trim_end(), trimmed[i+"STDMETHODCALLTYPE".len()..].trim_start()))
+            .filter(|(ret, after)| !ret.is_empty() && !after.starts_with("*"))              // exclude function pointers
+        {
+            if let Some((method_name, params)) = method_params.split_once_trim("(") {         // start of arguments list
+                let ret = ret.trim_start_matches("virtual").trim();
+                let (_prev, method_name) = method_name.try_rsplit_once_trim(" ");           // find start of method name
+                method = Some((ret.to_string(), method_name.to_string(), params.to_string()));
+            }
+        }
+
+        // STDMETHOD(QueryInterface)(
+        else if let Some((method_name, params)) = trimmed.split_once_trim("STDMETHOD(")
+            .and_then(|(_, after)| after.split_once_trim(")"))
+        {
+            method = Some(("HRESULT".into(), method_name.to_string(), params.trim_start_matches('(').to_string()));
+        }
+
+        // STDMETHOD_(ULONG, AddRef)(
+        else if let Some((ret_method, params)) = trimmed.split_once_trim("STDMETHOD_(")
+            .and_then(|(_, after)| after.split_once_trim(")"))
+        {
+            if let Some((ret, method_name)) = ret_method.rsplit_once_trim(",") {
+                method = Some((ret.to_string(), method_name.to_string(), params.trim_start_matches('(').to_string()));
+            }
+        }
+
+        let (ret, method_name, mut params) = method?;
+        if !Method::valid_name(&method_name) { return None }
+
+        // read until the closing `)` of the parameter list, which might be several lines later
+        let mut rest = line.get(trimmed.len()+1..).unwrap_or("").to_string();
+        let end = loop {
+            let mut depth = 1;
+            let end = params.char_indices().find_map(|(i, ch)| {
+                match ch { '(' => depth += 1, ')' => depth -= 1, _ => {} }
+                (depth == 0).then_some(i)
+            });
+            if end.is_some() { break end }
+            let next = if let Some(next) = src.next_line() { next } else { break None };
+            let next = next.trimmed.without_cpp_comments();
+            let (next, after) = next.split_once(';').unwrap_or((&next, ""));
+            params.push(' ');
+            params.push_str(next.trim());
+            rest = after.to_string();
+        };
+        params.truncate(end.unwrap_or(params.len()));
+
+        let mut method = Method::new(self.id.clone(), Ident::own(&method_name));
+        method.ret          = Some(ret);
+        method.params       = split_params(&params);
+        method.condition    = conditions.current();
+        method.f.defined_at.insert(location.clone());
+        self.add_method(location, method);
+        Some(rest)
+    }
+
+    /// Add a method, treating same-named methods as:
+    /// *   variants, if declared under different `#if` conditions (e.g. `ID3D12Heap::GetDesc` for `_MSC_VER` vs other compilers)
+    /// *   overloads, if their signatures differ
+    /// *   duplicates otherwise
     fn add_method(&mut self, location: &Location, method: Method) {
         debug_assert_eq!(method.ty, self.id);
-        if let Some(prev) = self.all_methods.insert(method.f.id.clone(), method) {
-            warning!(at: &location.path, line: location.line_no_or_0(), column: location.col_no_or_0(), "duplicate method `{}::{}`", prev.ty, prev.f.id);
+        let variant_of = self.all_methods.iter_mut().find(|m| m.f.id == method.f.id && m.condition.is_some() && method.condition.is_some() && m.condition != method.condition);
+        if let Some(prev) = variant_of {
+            prev.variants.push(method);
+        } else if let Some(prev) = self.all_methods.iter().find(|m| m.f.id == method.f.id && m.signature() == method.signature()) {
+            warning!(at: &location.path, line: location.line_no_or_0(), column: location.col_no_or_0(), "duplicate method `{}::{}{}`", prev.ty, prev.f.id, prev.signature());
+        } else {
+            self.all_methods.push(method);
+        }
+    }
+}
+
+/// Split `THIS_ REFIID riid, void** ppvObj` into `["REFIID riid", "void** ppvObj"]`, dropping `THIS`, `THIS_`, and `void`.
+fn split_params(params: &str) -> Vec<String> {
+    let params = params.trim();
+    let params = params.strip_prefix("THIS_").or_else(|| params.strip_prefix("THIS").filter(|p| p.trim().is_empty())).unwrap_or(params);
+    let mut out = Vec::new();
+    let mut param = String::new();
+    let mut depth = 0;
+    for ch in params.chars() {
+        match ch {
+            '(' | '[' | '<' => depth += 1,
+            ')' | ']' | '>' => depth -= 1,
+            ',' if depth == 0 => { out.push(std::mem::take(&mut param)); continue },
+            _ => {},
         }
+        param.push(ch);
     }
+    out.push(param);
+    let mut out = out.into_iter().map(|p| p.split_ascii_whitespace().collect::<Vec<_>>().join(" ")).filter(|p| !p.is_empty()).collect::<Vec<_>>();
+    if out == ["void"] { out.clear() }
+    out
+}
+
+
+
+#[test] fn test_overloads() {
+    let root = Root::from_cpp_src(r#"
+        MIDL_INTERFACE("00000000-0000-0000-C000-000000000046")
+        IUnknown
+        {
+        public:
+            virtual HRESULT STDMETHODCALLTYPE QueryInterface(
+                /* [in] */ REFIID riid,
+                /* [annotation][iid_is][out] */
+                _COM_Outptr_  void **ppvObject) = 0;
+            virtual ULONG STDMETHODCALLTYPE AddRef( void) = 0;
+            virtual ULONG STDMETHODCALLTYPE Release( void) = 0;
+        };
+
+        MIDL_INTERFACE("6b3b2502-6e51-45b3-90ee-9884265e8df3")
+        ID3D12Heap : public IUnknown
+        {
+        public:
+        #if defined(_MSC_VER) || !defined(_WIN32)
+            virtual D3D12_HEAP_DESC STDMETHODCALLTYPE GetDesc( void) = 0;
+        #else
+            virtual D3D12_HEAP_DESC *STDMETHODCALLTYPE GetDesc(
+                D3D12_HEAP_DESC * RetVal) = 0;
+        #endif
+        };
+
+        DECLARE_INTERFACE_(IOverloads, IUnknown)
+        {
+            STDMETHOD(QueryInterface)(THIS_ REFIID riid, void** ppvObj) PURE;
+            STDMETHOD_(ULONG,AddRef)(THIS) PURE;
+            STDMETHOD_(ULONG,Release)(THIS) PURE;
+
+            STDMETHOD_(void, Draw)(THIS_ UINT Count) PURE;
+            STDMETHOD_(void, Other)(THIS) PURE;
+            STDMETHOD_(void, Draw)(THIS_
+                _In_reads_(Count) CONST D3D12_RECT *pRects,
+                UINT Count) PURE;
+            STDMETHOD_(void, Draw)(THIS_ UINT Count2) PURE;
+        };
+
+        DECLARE_INTERFACE_(ID2D1RenderTargetLike, IUnknown)
+        {
+            STDMETHOD_(void, DrawBitmap)(
+                _In_ ID2D1Bitmap *bitmap,
+                _In_opt_ CONST D2D1_RECT_F *destinationRectangle = NULL,
+                FLOAT opacity = 1.0f,
+                D2D1_BITMAP_INTERPOLATION_MODE interpolationMode = D2D1_BITMAP_INTERPOLATION_MODE_LINEAR,
+                _In_opt_ CONST D2D1_RECT_F *sourceRectangle = NULL
+                ) PURE;
+            STDMETHOD_(void, Fill)(THIS_ const UINT) PURE;
+            STDMETHOD_(void, Fill)(THIS_ const INT) PURE;
+            STDMETHOD_(void, Fill)(THIS_ unsigned int, float [4]) PURE;
+
+            COM_DECLSPEC_NOTHROW
+            void
+            DrawBitmap(
+                _In_ ID2D1Bitmap *bitmap,
+                const D2D1_RECT_F &destinationRectangle,
+                FLOAT opacity = 1.0f
+                )
+            {
+                DrawBitmap(bitmap, &destinationRectangle, opacity);
+            }
+
+            COM_DECLSPEC_NOTHROW void Fill(THIS) { Fill(0u); }
+
+            STDMETHOD_(void, AfterInline)(THIS) PURE;
+        };
+
+        DECLARE_INTERFACE_(IBaseRT, IUnknown)
+        {
+            STDMETHOD(CreateBitmap)(THIS_ D2D1_SIZE_U size, _In_opt_ CONST void *srcData, UINT32 pitch, _In_ CONST D2D1_BITMAP_PROPERTIES *bitmapProperties, _COM_Outptr_ ID2D1Bitmap **bitmap) PURE;
+        };
+
+        DECLARE_INTERFACE_(IDerivedDC, IBaseRT)
+        {
+            STDMETHOD(CreateBitmap)(THIS_ D2D1_SIZE_U size, _In_opt_ CONST void *sourceData, UINT32 pitch, _In_ CONST D2D1_BITMAP_PROPERTIES1 *bitmapProperties, _COM_Outptr_ ID2D1Bitmap1 **bitmap) PURE;
+            STDMETHOD_(void, Other)(THIS) PURE;
+        };
+    "#);
+
+    let heap = root.interfaces.get("ID3D12Heap").unwrap();
+    let get_desc = heap.overloads("GetDesc").collect::<Vec<_>>();
+    assert_eq!(get_desc.len(), 1);
+    assert_eq!((get_desc[0].ret.as_deref(), get_desc[0].signature(), get_desc[0].condition.as_deref()), (Some("D3D12_HEAP_DESC"), "()".into(), Some("(defined(_MSC_VER) || !defined(_WIN32))")));
+    let variant = &get_desc[0].variants[0];
+    assert_eq!((variant.ret.as_deref(), variant.signature(), variant.condition.as_deref()), (Some("D3D12_HEAP_DESC *"), "(D3D12_HEAP_DESC*)".into(), Some("!(defined(_MSC_VER) || !defined(_WIN32))")));
+
+    let iunknown = root.interfaces.get("IUnknown").unwrap();
+    assert_eq!(iunknown.method("QueryInterface").unwrap().params, ["REFIID riid", "_COM_Outptr_ void **ppvObject"]);
+    assert_eq!(iunknown.method("QueryInterface").unwrap().signature(), "(REFIID, void**)");
+
+    let overloads = root.interfaces.get("IOverloads").unwrap();
+    let draw = overloads.overloads("Draw").map(|m| m.signature()).collect::<Vec<_>>();
+    assert_eq!(draw, ["(UINT)", "(const D3D12_RECT*, UINT)"]);
+
+    let d2d1 = root.interfaces.get("ID2D1RenderTargetLike").unwrap();
+    assert_eq!(d2d1.method("DrawBitmap").unwrap().signature(), "(ID2D1Bitmap*, const D2D1_RECT_F*, FLOAT, D2D1_BITMAP_INTERPOLATION_MODE, const D2D1_RECT_F*)");
+    let fill = d2d1.overloads("Fill").map(|m| m.signature()).collect::<Vec<_>>();
+    assert_eq!(fill, ["(const UINT)", "(const INT)", "(unsigned int, float[4])"]);
+    assert!(d2d1.method("AfterInline").is_some());
+
+    let vtable = root.vtable("IOverloads").unwrap().iter().map(|s| format!("{}{}", s.method.f.id, s.method.signature())).collect::<Vec<_>>();
+    assert_eq!(vtable, [
+        "QueryInterface(REFIID, void**)",
+        "AddRef()",
+        "Release()",
+        "Draw(const D3D12_RECT*, UINT)",
+        "Draw(UINT)",
+        "Other()",
+    ]);
+
+    let vtable = root.vtable("IDerivedDC").unwrap().iter().map(|s| format!("{}:{}{}", s.declared_by, s.method.f.id, s.method.signature())).collect::<Vec<_>>();
+    assert_eq!(vtable, [
+        "IUnknown:QueryInterface(REFIID, void**)",
+        "IUnknown:AddRef()",
+        "IUnknown:Release()",
+        "IBaseRT:CreateBitmap(D2D1_SIZE_U, const void*, UINT32, const D2D1_BITMAP_PROPERTIES*, ID2D1Bitmap**)",
+        "IDerivedDC:CreateBitmap(D2D1_SIZE_U, const void*, UINT32, const D2D1_BITMAP_PROPERTIES1*, ID2D1Bitmap1**)",
+        "IDerivedDC:Other()",
+    ]);
+}
+
+
+#[test] fn test_interface_kinds() {
+    let root = Root::from_cpp_src(r#"
+        DECLARE_INTERFACE(IUnknown) { STDMETHOD_(ULONG, AddRef)(THIS) PURE; };
+        DECLARE_INTERFACE_(IInspectable, IUnknown) { STDMETHOD(GetRuntimeClassName)(THIS_ HSTRING* className) PURE; };
+        DECLARE_INTERFACE_(IXAudio2, IUnknown) { STDMETHOD(StartEngine)(THIS) PURE; };
+        DECLARE_INTERFACE_(IXAudio2Extension, IXAudio2) { STDMETHOD_(void, GetProcessingQuantum)(THIS_ UINT32* q) PURE; };
+        DECLARE_INTERFACE_(IWinRtThing, IInspectable) { STDMETHOD(Thing)(THIS) PURE; };
+        DECLARE_INTERFACE(IXAudio2VoiceCallback) { STDMETHOD_(void, OnVoiceProcessingPassEnd)(THIS) PURE; };
+        DECLARE_INTERFACE_(IDerivedCallback, IXAudio2VoiceCallback) { STDMETHOD_(void, OnMore)(THIS) PURE; };
+        DECLARE_INTERFACE_(IOrphan, IMissing) { STDMETHOD(Orphan)(THIS) PURE; };
+    "#);
+
+    let kinds = root.interfaces.values_by_insert().map(|i| (i.id.as_str(), i.kind)).collect::<Vec<_>>();
+    assert_eq!(kinds, [
+        ("IUnknown",                InterfaceKind::Com),
+        ("IInspectable",            InterfaceKind::WinRt),
+        ("IXAudio2",                InterfaceKind::Com),
+        ("IXAudio2Extension",       InterfaceKind::Com),
+        ("IWinRtThing",             InterfaceKind::WinRt),
+        ("IXAudio2VoiceCallback",   InterfaceKind::Vtable),
+        ("IDerivedCallback",        InterfaceKind::Vtable),
+        ("IOrphan",                 InterfaceKind::UnknownBase),
+    ]);
+    assert!(!InterfaceKind::Vtable.is_com());
+}
+
+#[test] fn test_typedef_interfaces() {
+    let root = Root::from_cpp_src(r#"
+        typedef interface IUnknown IUnknown;
+        typedef interface IUnknown
+        {
+            virtual HRESULT STDMETHODCALLTYPE QueryInterface(REFIID riid, void **ppvObject) = 0;
+            virtual ULONG STDMETHODCALLTYPE AddRef( void) = 0;
+            virtual ULONG STDMETHODCALLTYPE Release( void) = 0;
+        } IUnknown;
+
+        typedef interface DECLSPEC_UUID("00000000-0000-0000-0000-000000000001") IFoo : public IUnknown {
+            STDMETHOD(Foo)(THIS_ UINT a) PURE;
+        } IFoo;
+
+        typedef interface IFoo
+        {
+            CONST_VTBL struct IFooVtbl *lpVtbl;
+        } IFoo;
+    "#);
+
+    let summary = root.interfaces.values_by_insert().map(|i| (
+        i.id.as_str(),
+        i.base.as_ref().map(|b| b.as_str()),
+        i.iid.map(|iid| iid.to_string()),
+        i.methods().map(|m| m.f.id.as_str()).collect::<Vec<_>>(),
+        i.kind,
+    )).collect::<Vec<_>>();
+    assert_eq!(summary, [
+        ("IUnknown",    None,               None,                                               vec!["AddRef", "QueryInterface", "Release"],    InterfaceKind::Com),
+        ("IFoo",        Some("IUnknown"),   Some("00000000-0000-0000-0000-000000000001".into()), vec!["Foo"],                                    InterfaceKind::Com),
+    ]);
+}
+
+#[test] fn test_duplicate_interfaces() {
+    let root = Root::from_cpp_src(r#"
+        MIDL_INTERFACE("00000000-0000-0000-C000-000000000046")
+        IUnknown
+        {
+        public:
+            virtual HRESULT STDMETHODCALLTYPE QueryInterface(REFIID riid, _COM_Outptr_ void **ppvObject) = 0;
+            virtual ULONG STDMETHODCALLTYPE AddRef( void) = 0;
+            virtual ULONG STDMETHODCALLTYPE Release( void) = 0;
+        };
+
+        MIDL_INTERFACE("8BA5FB08-5195-40e2-AC58-0D989C3A0102")
+        ID3D10Blob : public IUnknown
+        {
+        public:
+            virtual LPVOID STDMETHODCALLTYPE GetBufferPointer( void) = 0;
+            virtual SIZE_T STDMETHODCALLTYPE GetBufferSize( void) = 0;
+        };
+
+        DECLARE_INTERFACE_(ID3D10Blob, IUnknown)
+        {
+            STDMETHOD(QueryInterface)(THIS_ REFIID iid, LPVOID *ppv) PURE;
+            STDMETHOD_(ULONG, AddRef)(THIS) PURE;
+            STDMETHOD_(ULONG, Release)(THIS) PURE;
+            STDMETHOD_(LPVOID, GetBufferPointer)(THIS) PURE;
+            STDMETHOD_(SIZE_T, GetBufferSize)(THIS) PURE;
+        };
+
+        DECLARE_INTERFACE_(ISame, IUnknown)
+        {
+            STDMETHOD(First)(THIS) PURE;
+            STDMETHOD(Second)(THIS_ UINT a) PURE;
+        };
+
+        MIDL_INTERFACE("00000000-0000-0000-0000-000000000001")
+        ISame : public IUnknown
+        {
+        public:
+            virtual HRESULT STDMETHODCALLTYPE First( void) = 0;
+            virtual HRESULT STDMETHODCALLTYPE Second( _In_ UINT b) = 0;
+        };
+
+        DECLARE_INTERFACE_(IReordered, IUnknown)
+        {
+            STDMETHOD(First)(THIS) PURE;
+            STDMETHOD(Second)(THIS) PURE;
+        };
+
+        DECLARE_INTERFACE_(IReordered, IUnknown)
+        {
+            STDMETHOD(Second)(THIS) PURE;
+            STDMETHOD(First)(THIS) PURE;
+        };
+
+        MIDL_INTERFACE("00000000-0000-0000-0000-000000000002")
+        IChanged : public IUnknown
+        {
+        public:
+            virtual HRESULT STDMETHODCALLTYPE First( void) = 0;
+            virtual HRESULT STDMETHODCALLTYPE Second( UINT a) = 0;
+        };
+
+        MIDL_INTERFACE("00000000-0000-0000-0000-000000000003")
+        IChanged : public IDispatch
+        {
+        public:
+            virtual HRESULT STDMETHODCALLTYPE First( void) = 0;
+            virtual HRESULT STDMETHODCALLTYPE Second( float a) = 0;
+            virtual HRESULT STDMETHODCALLTYPE Third( void) = 0;
+        };
+    "#);
+
+    let issues = |id: &str| root.interfaces.get(id).unwrap().issues.iter().map(|i| i.message.split_once(": ").unwrap().1.to_string()).collect::<Vec<_>>();
+    assert!(issues("ISame").is_empty());
+    assert!(issues("ID3D10Blob").is_empty(), "{:?}", issues("ID3D10Blob"));
+    assert_eq!(root.interfaces.get("ISame").unwrap().iid, Guid::parse("00000000-0000-0000-0000-000000000001"));
+    assert_eq!(issues("IReordered"), [
+        "method 0 is `First()` vs `Second()` (same methods in a different order)",
+        "method 1 is `Second()` vs `First()` (same methods in a different order)",
+    ]);
+    assert_eq!(issues("IChanged"), [
+        "base Some(\"IUnknown\") vs Some(\"IDispatch\")",
+        "IID 00000000-0000-0000-0000-000000000002 vs 00000000-0000-0000-0000-000000000003",
+        "method 1 is `Second(UINT)` vs `Second(float)`",
+        "method `Third` only in redefinition",
+    ]);
 }
@@ -14,6 +14,12 @@ pub struct Macro {
     /// Identifier of this macro (e.g. `ID`)
     pub id:                     Ident,
 
+    /// Parameters of this macro (e.g. `a`, `b` for `#define ID(a, b) ...`)
+    pub params:                 Vec<Ident>,
+
+    /// The body of this macro, sans comments and line continuations
+    pub body:                   String,
+
     pub(crate) _non_exhaustive: (),
 }
 
@@ -22,6 +28,8 @@ impl Macro {
         Self {
             defined_at: Default::default(),
             id: id.into(),
+            params: Default::default(),
+            body: Default::default(),
             _non_exhaustive: Default::default(),
         }
     }
@@ -29,6 +37,6 @@ impl Macro {
 
 impl Debug for Macro {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "Macro {{ id: {:?}, .. }}", self.id)
+        write!(f, "Macro {{ id: {:?}, params: {:?}, .. }}", self.id, self.params)
     }
 }
@@ -13,20 +13,134 @@ pub struct Method {
     /// The function data - e.g. `f.id` might be `AddRef`.
     pub f:                      Function,
 
+    pub kind:                   MethodKind,
+
+    /// The return type, such as `HRESULT`, if known.
+    pub ret:                    Option<String>,
+
+    /// The parameters as written, excluding `THIS` / `THIS_`, such as `["REFIID riid", "_COM_Outptr_ void **ppvObject"]`.
+    pub params:                 Vec<String>,
+
+    /// The `#if` condition this method was declared under, if any.
+    pub condition:              Option<String>,
+
+    /// Alternative declarations of this method under other `#if` conditions.
+    pub variants:               Vec<Method>,
+
     /// The method is inherited from a base class.
     pub(crate) inherited:       AtomicBool,
 
     pub(crate) _non_exhaustive: (),
 }
 
+/// What kind of member function a [`Method`] is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MethodKind {
+    /// A regular method such as `AddRef`
+    Method,
+
+    /// e.g. `CD3DX12_RECT() = default;`
+    Constructor,
+
+    /// e.g. `~CD3DX12_RECT() {}`
+    Destructor,
+
+    /// e.g. `operator const D3D12_RECT&() const`, `operator==`
+    Operator,
+}
+
 impl Method {
     pub fn valid_name(name: &str) -> bool { valid_name(name) }
-    pub fn new(ty: Ident, id: Ident) -> Self { Self { ty, f: Function::new(id), inherited: AtomicBool::new(false), _non_exhaustive: () } }
+    pub fn new(ty: Ident, id: Ident) -> Self {
+        Self { ty, f: Function::new(id), kind: MethodKind::Method, ret: None, params: Vec::new(), condition: None, variants: Vec::new(), inherited: AtomicBool::new(false), _non_exhaustive: () }
+    }
+
     pub(crate) fn is_inherited(&self) -> bool { self.inherited.load(Ordering::Relaxed) }
+
+    /// The parameter types, without names, SAL annotations, or comments, such as `(REFIID, void**)`.
+    /// Used to tell overloads apart.
+    pub fn signature(&self) -> String {
+        format!("({})", self.params.iter().map(|p| param_type(p)).collect::<Vec<_>>().join(", "))
+    }
+}
+
+/// Pointer typedefs that restated methods use interchangeably with the types they expand to (e.g. `LPVOID *ppv` vs `void **ppvObject`).
+const POINTER_TYPEDEFS : &[(&str, &[&str])] = &[
+    ("LPVOID",      &["void", "*"]),
+    ("PVOID",       &["void", "*"]),
+    ("LPCVOID",     &["const", "void", "*"]),
+    ("LPUNKNOWN",   &["IUnknown", "*"]),
+    ("LPSTR",       &["CHAR", "*"]),
+    ("LPCSTR",      &["const", "CHAR", "*"]),
+    ("LPWSTR",      &["WCHAR", "*"]),
+    ("LPCWSTR",     &["const", "WCHAR", "*"]),
+    ("LPOLESTR",    &["OLECHAR", "*"]),
+    ("LPCOLESTR",   &["const", "OLECHAR", "*"]),
+];
+
+/// Reduce e.g. `_In_reads_(n) const D3D12_RECT *pRects` to `const D3D12_RECT*`
+pub(crate) fn param_type(param: &str) -> String {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    for ch in param.chars() {
+        if ch.is_ascii_word_character() { token.push(ch); continue }
+        if !token.is_empty() { tokens.push(std::mem::take(&mut token)); }
+        if !ch.is_ascii_whitespace() { tokens.push(ch.to_string()); }
+    }
+    if !token.is_empty() { tokens.push(token); }
+
+    // drop SAL annotations (`_In_`, `_Out_writes_(n)`) and `__RPC_FAR`-style decorations
+    let mut kept = Vec::new();
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(t) = tokens.next() {
+        if t.starts_with('_') {
+            if tokens.peek().map(|t| t.as_str()) == Some("(") {
+                let mut depth = 0;
+                for t in tokens.by_ref() {
+                    match t.as_str() { "(" => depth += 1, ")" => { depth -= 1; if depth == 0 { break } }, _ => {} }
+                }
+            }
+            continue
+        }
+        if ["IN", "OUT", "OPTIONAL"].contains(&t.as_str()) { continue }
+        if let Some((_, expanded)) = POINTER_TYPEDEFS.iter().find(|(typedef, _)| *typedef == t) {
+            kept.extend(expanded.iter().map(|t| t.to_string()));
+            continue
+        }
+        kept.push(if t == "CONST" { "const".into() } else { t });
+    }
+
+    // drop default arguments (e.g. `FLOAT opacity = 1.0f`)
+    let mut depth = 0;
+    let default = kept.iter().position(|t| {
+        match t.as_str() { "(" | "[" | "<" => depth += 1, ")" | "]" | ">" => depth -= 1, _ => {} }
+        t == "=" && depth == 0
+    });
+    if let Some(default) = default { kept.truncate(default); }
+
+    // drop the parameter name (the last identifier before any array dimensions), unless it's part of the type (e.g. `const UINT`, `unsigned int`)
+    let dims = kept.iter().position(|t| t == "[").unwrap_or(kept.len());
+    let word = |t: &String| t.starts_with(|ch: char| ch.is_ascii_word_character());
+    let qualifier = |t: &String| ["const", "volatile", "struct", "union", "class", "enum"].contains(&t.as_str());
+    if let Some((last, before)) = kept[..dims].split_last() {
+        let named = word(last) && !is_type_keyword(last) && (before.last().is_some_and(|t| t == "*" || t == "&") || before.iter().any(|t| word(t) && !qualifier(t)));
+        if named { kept.remove(dims - 1); }
+    }
+
+    let mut out = String::new();
+    for t in kept {
+        let word = t.starts_with(|ch: char| ch.is_ascii_word_character());
+        if word && out.ends_with(|ch: char| ch.is_ascii_word_character() || ch == '*' || ch == '&') { out.push(' ') }
+        out.push_str(&t);
+    }
+    out
 }
 
 impl Debug for Method {
     fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
-        write!(fmt, "Method {{ ty: {:?}, f.id: {:?}, inherited: {}, ... }}", self.ty, self.f.id, self.inherited.load(Ordering::Relaxed))
+        write!(fmt, "Method {{ ty: {:?}, f.id: {:?}", self.ty, self.f.id)?;
+        if self.kind != MethodKind::Method { write!(fmt, ", kind: {:?}", self.kind)?; }
+        if !self.params.is_empty() { write!(fmt, ", signature: {:?}", self.signature())?; }
+        write!(fmt, ", inherited: {}, ... }}", self.inherited.load(Ordering::Relaxed))
     }
 }
@@ -0,0 +1,120 @@
+use crate::*;
+
+use std::fmt::{self, Debug, Formatter};
+
+
+
+/// `#define IFoo_Method(This, ...) ( (This)->lpVtbl -> Method(This, ...) )` - a C macro wrapping an interface method.
+pub struct MethodWrapper {
+    /// The macro itself, such as `IUnknown_QueryInterface`.
+    pub macro_:                 Macro,
+
+    /// The method this wrapper is named after, such as `QueryInterface`.
+    pub method:                 Ident,
+
+    /// The method this wrapper actually calls, if recognized (e.g. `QueryInterface` for `(This)->lpVtbl -> QueryInterface(This,riid,ppvObject)`.)
+    pub target:                 Option<Ident>,
+
+    pub(crate) _non_exhaustive: (),
+}
+
+impl MethodWrapper {
+    pub fn new(macro_: Macro, method: Ident) -> Self {
+        let target = call_target(&macro_.body).map(Ident::own);
+        Self { macro_, method, target, _non_exhaustive: () }
+    }
+}
+
+impl Debug for MethodWrapper {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("MethodWrapper")
+            .field("macro_",        &self.macro_.id     )
+            .field("method",        &self.method        )
+            .field("target",        &self.target        )
+            .finish_non_exhaustive()
+    }
+}
+
+/// Find `Method` in `( (This)->lpVtbl -> Method(This,a) )` or `(p)->Method(a)`
+fn call_target(body: &str) -> Option<&str> {
+    let (_, after) = body.rsplit_once("->")?;
+    let after = after.trim_start();
+    let end = after.find(|ch: char| !ch.is_ascii_word_character()).unwrap_or(after.len());
+    let (target, rest) = after.split_at(end);
+    (valid_name(target) && rest.trim_start().starts_with('(')).then_some(target)
+}
+
+
+
+#[test] fn test_call_target() {
+    assert_eq!(call_target("( (This)->lpVtbl -> QueryInterface(This,riid,ppvObject) )"),    Some("QueryInterface"));
+    assert_eq!(call_target("(p)->lpVtbl->AddRef(p)"),                                       Some("AddRef"));
+    assert_eq!(call_target("(p)->Release()"),                                               Some("Release"));
+    assert_eq!(call_target("IUnknown_Release(p)"),                                          None);
+}
+
+#[test] fn test_method_wrappers() {
+    let root = Root::from_cpp_src(r#"
+        DECLARE_INTERFACE(IUnknown)
+        {
+            STDMETHOD(QueryInterface)(THIS_ REFIID riid, void** ppvObj) PURE;
+            STDMETHOD_(ULONG,AddRef)(THIS) PURE;
+            STDMETHOD_(ULONG,Release)(THIS) PURE;
+        };
+
+        DECLARE_INTERFACE_(IDirect3D9, IUnknown)
+        {
+            STDMETHOD(QueryInterface)(THIS_ REFIID riid, void** ppvObj) PURE;
+            STDMETHOD_(ULONG,AddRef)(THIS) PURE;
+            STDMETHOD_(ULONG,Release)(THIS) PURE;
+            STDMETHOD_(UINT, GetAdapterCount)(THIS) PURE;
+            STDMETHOD(GetAdapterIdentifier)(THIS_ UINT Adapter,DWORD Flags,D3DADAPTER_IDENTIFIER9* pIdentifier) PURE;
+            STDMETHOD(RegisterSoftwareDevice)(THIS_ void* pInitializeFunction) PURE;
+        };
+
+        #define IDirect3D9_QueryInterface(p,a,b) (p)->lpVtbl->QueryInterface(p,a,b)
+        #define IDirect3D9_AddRef(p) (p)->lpVtbl->AddRef(p)
+        #define IDirect3D9_Release(p) (p)->lpVtbl->AddRef(p)
+        #define IDirect3D9_GetAdapterCount(p) (p)->lpVtbl->GetAdapterCount(p)
+        #define IDirect3D9_GetAdapterIdentifier(p,a,b) (p)->lpVtbl->GetAdapterIdentifier(p,a,b)
+        #define D3D_SDK_VERSION_MACRO(x) (x)
+
+        DECLARE_INTERFACE_(IShellLinkW, IUnknown)
+        {
+            STDMETHOD(GetPath)(THIS_ LPWSTR pszFile, int cch) PURE;
+            STDMETHOD(SetPath)(THIS_ LPCWSTR pszFile) PURE;
+        };
+
+        #define IShellLink_GetPath(p,a,b) (p)->lpVtbl->GetPath(p,a,b)
+    "#);
+
+    let d3d9 = root.interfaces.get("IDirect3D9").unwrap();
+    let wrappers = d3d9.macro_wrappers.iter().map(|w| (w.macro_.id.as_str(), w.method.as_str(), w.target.as_ref().map(|t| t.as_str()))).collect::<Vec<_>>();
+    assert_eq!(wrappers, [
+        ("IDirect3D9_GetAdapterCount",      "GetAdapterCount",      Some("GetAdapterCount")),
+        ("IDirect3D9_GetAdapterIdentifier", "GetAdapterIdentifier", Some("GetAdapterIdentifier")),
+        ("IDirect3D9_QueryInterface",       "QueryInterface",       Some("QueryInterface")),
+        ("IDirect3D9_AddRef",               "AddRef",               Some("AddRef")),
+        ("IDirect3D9_Release",              "Release",              Some("AddRef")),
+    ]);
+
+    let issues = d3d9.issues.iter().map(|i| i.message.as_str()).collect::<Vec<_>>();
+    assert_eq!(issues, [
+        "missing C macro wrapper `IDirect3D9_RegisterSoftwareDevice`",
+        "C macro `IDirect3D9_GetAdapterIdentifier` takes 3 arguments, but `IDirect3D9::GetAdapterIdentifier` needs 4 (including `This`)",
+        "C macro `IDirect3D9_Release` calls `AddRef` instead of `Release`",
+    ]);
+
+    let shell_link = root.interfaces.get("IShellLinkW").unwrap();
+    assert_eq!(shell_link.macro_wrappers.iter().map(|w| w.macro_.id.as_str()).collect::<Vec<_>>(), ["IShellLink_GetPath"]);
+    let issues = shell_link.issues.iter().map(|i| i.message.as_str()).collect::<Vec<_>>();
+    assert_eq!(issues, [
+        "missing C macro wrapper `IShellLink_SetPath`",
+        "missing C macro wrapper `IShellLink_QueryInterface`",
+        "missing C macro wrapper `IShellLink_AddRef`",
+        "missing C macro wrapper `IShellLink_Release`",
+    ]);
+
+    assert!(root.interfaces.get("IUnknown").unwrap().macro_wrappers.is_empty());
+    assert_eq!(root.macros.keys().map(|k| k.as_str()).collect::<Vec<_>>(), ["D3D_SDK_VERSION_MACRO"]);
+}
@@ -0,0 +1,114 @@
+use crate::*;
+
+
+
+/// The `#pragma pack` state of a single file.
+///
+/// Tracks `#pragma pack(...)` as well as `#include <pshpackN.h>` / `#include <poppack.h>`.
+/// Only the first branch of each `#if` / `#elif` / `#else` affects the packing after `#endif`.
+#[derive(Default)]
+pub(crate) struct PackStack {
+    /// The current packing, or [`None`] for the compiler default.
+    pub current:    Option<u32>,
+    stack:          Vec<Pushed>,
+    branches:       FirstBranch<(Option<u32>, Vec<Pushed>)>,
+}
+
+/// A `push`ed identifier (if any) and the packing to restore on `pop`.
+type Pushed = (Option<String>, Option<u32>);
+
+impl PackStack {
+    /// Apply a preprocessor directive (the text after `#`, e.g. `ifdef _WIN64`), ignoring non-conditionals.
+    pub fn conditional(&mut self, directive: &str) {
+        let mut state = (self.current, std::mem::take(&mut self.stack));
+        self.branches.directive(directive, &mut state);
+        (self.current, self.stack) = state;
+    }
+
+    /// Apply the rest of a `#pragma` line (e.g. `pack(push, 1)`), ignoring non-`pack` pragmas.
+    pub fn pragma(&mut self, pragma: &str) -> Result<(), String> {
+        let pragma = pragma.without_cpp_comments();
+        let args = if let Some(args) = pragma.trim().strip_prefix("pack") { args.trim() } else { return Ok(()) };
+        let args = args.strip_prefix('(').and_then(|a| a.strip_suffix(')')).ok_or_else(|| format!("expected `#pragma pack(...)`, got `#pragma {}`", pragma.trim()))?;
+        let mut args = args.split(',').map(|a| a.trim()).filter(|a| !a.is_empty()).collect::<Vec<_>>();
+
+        let action = args.first().copied();
+        if action == Some("push") || action == Some("pop") { args.remove(0); }
+        let n = match args.last().map(|a| a.parse_cpp_int()) {
+            Some(Some(n)) => { args.pop(); Some(Self::valid(n)?) },
+            _ => None,
+        };
+        let id = match args.as_slice() {
+            []      => None,
+            [id]    => Some(id.to_string()),
+            _       => return Err(format!("unexpected `#pragma {}`", pragma.trim())),
+        };
+
+        match action {
+            Some("push")                    => { self.stack.push((id, self.current)); if n.is_some() { self.current = n; } },
+            Some("pop")                     => { self.pop(id.as_deref())?; if n.is_some() { self.current = n; } },
+            Some("show")                    => {},
+            _ if id.is_some()               => return Err(format!("unexpected `#pragma {}`", pragma.trim())),
+            _                               => self.current = n, // `pack()` resets to the default, `pack(n)` sets it
+        }
+        Ok(())
+    }
+
+    /// Apply an `#include <...>`, handling `pshpackN.h` and `poppack.h`.
+    pub fn include(&mut self, include: &str) -> Result<(), String> {
+        let header = include.without_cpp_comments();
+        let header = header.trim().trim_matches(|ch| ch == '<' || ch == '>' || ch == '"').to_ascii_lowercase();
+        if header == "poppack.h" {
+            self.pop(None)
+        } else if let Some(n) = header.strip_prefix("pshpack").and_then(|h| h.strip_suffix(".h")) {
+            let n = Self::valid(n.parse_cpp_int().ok_or_else(|| format!("unexpected `#include <{}>`", header))?)?;
+            self.stack.push((None, self.current));
+            self.current = Some(n);
+            Ok(())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn pop(&mut self, id: Option<&str>) -> Result<(), String> {
+        if let Some(id) = id {
+            let idx = self.stack.iter().rposition(|(i, _)| i.as_deref() == Some(id)).ok_or_else(|| format!("`#pragma pack(pop, {})` without matching push", id))?;
+            self.current = self.stack[idx].1;
+            self.stack.truncate(idx);
+        } else {
+            self.current = self.stack.pop().ok_or("`#pragma pack(pop)` without matching push")?.1;
+        }
+        Ok(())
+    }
+
+    fn valid(n: u64) -> Result<u32, String> {
+        match n {
+            1 | 2 | 4 | 8 | 16  => Ok(n as u32),
+            _                   => Err(format!("invalid packing `{}`", n)),
+        }
+    }
+}
+
+
+
+#[test] fn test_pack_stack() {
+    let mut pack = PackStack::default();
+    pack.pragma("pack(push, 1)").unwrap();                  assert_eq!(pack.current, Some(1));
+    pack.include("<pshpack4.h>").unwrap();                  assert_eq!(pack.current, Some(4));
+    pack.pragma("pack(push, outer, 8) // comment").unwrap();assert_eq!(pack.current, Some(8));
+    pack.pragma("pack(2)").unwrap();                        assert_eq!(pack.current, Some(2));
+    pack.pragma("pack(pop, outer)").unwrap();               assert_eq!(pack.current, Some(4));
+    pack.include("<poppack.h>").unwrap();                   assert_eq!(pack.current, Some(1));
+    pack.pragma("warning(disable: 4201)").unwrap();         assert_eq!(pack.current, Some(1));
+    pack.pragma("pack(pop)").unwrap();                      assert_eq!(pack.current, None);
+    assert!(pack.pragma("pack(pop)").is_err());
+    assert!(pack.pragma("pack(3)").is_err());
+
+    pack.conditional("ifdef");
+    pack.include("<pshpack1.h>").unwrap();                  assert_eq!(pack.current, Some(1));
+    pack.conditional("else");                               assert_eq!(pack.current, None);
+    pack.include("<pshpack2.h>").unwrap();                  assert_eq!(pack.current, Some(2));
+    pack.conditional("endif");                              assert_eq!(pack.current, Some(1));
+    pack.include("<poppack.h>").unwrap();                   assert_eq!(pack.current, None);
+    assert!(pack.include("<poppack.h>").is_err());
+}
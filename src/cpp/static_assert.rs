@@ -0,0 +1,46 @@
+use crate::*;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Debug, Formatter};
+
+
+
+/// `C_ASSERT(sizeof(FOO) == 24);` or `static_assert(sizeof(FOO) == 24, "...");`
+pub struct StaticAssert {
+    pub defined_at:             Location,
+
+    /// The asserted expression, as written (e.g. `sizeof ( FOO ) == 24`)
+    pub expr:                   String,
+
+    /// The message of a `static_assert`, if any.
+    pub message:                Option<String>,
+
+    /// Types referenced by `sizeof(...)`, `FIELD_OFFSET(...)`, `TYPE_ALIGNMENT(...)`, etc.
+    pub types:                  BTreeSet<Ident>,
+
+    /// The preprocessor condition this assert was declared under (e.g. `defined(_WIN64)`)
+    pub condition:              Option<String>,
+
+    /// The result of `expr` for each [`Arch`] it could be evaluated for, skipping those for which [`StaticAssert::condition`] doesn't hold.
+    pub results:                BTreeMap<Arch, bool>,
+
+    pub(crate) _non_exhaustive: (),
+}
+
+impl StaticAssert {
+    pub fn new(defined_at: Location, expr: impl Into<String>) -> Self {
+        Self { defined_at, expr: expr.into(), message: None, types: Default::default(), condition: None, results: Default::default(), _non_exhaustive: () }
+    }
+
+    /// Macros and keywords which take a type as their first argument.
+    pub(crate) const TYPE_OPERATORS : &'static [&'static str] = &["sizeof", "FIELD_OFFSET", "offsetof", "RTL_FIELD_SIZE", "TYPE_ALIGNMENT", "__alignof", "_alignof", "alignof"];
+}
+
+impl Debug for StaticAssert {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(fmt, "StaticAssert {{ expr: {:?}", self.expr)?;
+        if let Some(message) = self.message.as_ref() { write!(fmt, ", message: {:?}", message)?; }
+        if let Some(condition) = self.condition.as_ref() { write!(fmt, ", condition: {:?}", condition)?; }
+        write!(fmt, ", results: {:?}, .. }}", self.results)
+    }
+}
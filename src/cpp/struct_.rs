@@ -30,12 +30,34 @@ pub struct Aggregate {
 #[derive(Default)]
 pub struct AggregateData {
     pub category:               AggregateCategory,
+
+    /// The base type, if any (e.g. `D3D12_RESOURCE_DESC` for `struct CD3DX12_RESOURCE_DESC : public D3D12_RESOURCE_DESC { ... }`)
     pub base:                   Option<Ident>,
+
+    /// The `#pragma pack` in effect where this aggregate was defined, or [`None`] for the compiler default.
+    pub pack:                   Option<u32>,
+
+    /// The `DECLSPEC_ALIGN(n)` / `__declspec(align(n))` of this aggregate, if any.
+    pub align:                  Option<u32>,
+
     pub fields:                 VecMap<Ident, Field>,
+
+    /// Constructors, destructors, methods, and operators, in declaration order.  These don't affect data layout.
+    pub methods:                Vec<Method>,
+
     pub issues:                 Vec<Issue>,
     pub(crate) _non_exhaustive: (),
 }
 
+/// A [`Field`] as accessed from C, possibly through anonymous `struct`s and `union`s.
+#[derive(Clone, Debug)]
+pub struct EffectiveField<'a> {
+    pub field:  &'a Field,
+
+    /// The anonymous aggregate [`Field`]s containing `field`, outermost first.
+    pub via:    Vec<&'a Field>,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum AggregateCategory {
     Class,
@@ -95,7 +117,9 @@ impl Aggregate {
     pub(crate) fn add_from_cpp(&mut self, start: SrcToken, src: &mut SrcReader, typedef: bool) -> Result<(), ()> {
         let start_loc = src.token_to_location(start);
         self.defined_at.insert(start_loc.clone());
-        self.data.add_from_cpp(start, src)?;
+        let result = self.data.add_from_cpp(start, src);
+        for m in self.data.methods.iter_mut() { m.ty = self.id.clone(); }
+        result?;
 
         macro_rules! expect_token { () => {
             src.next_token().ok_or_else(||{
@@ -182,18 +206,43 @@ impl AggregateData {
             })?
         }}
 
+        let mut conditions = Conditions::default();
         'struct_: loop {
             let mut token = expect_token!();
             while token == "#" {
                 let rest_of_line = src.next_line();
                 let rest_of_line = rest_of_line.as_ref().map_or("", |l| &**l);
-                issue!(token, log, "preprocessor command inside `{} {{ ... }}` not supported: #{}", self.category, rest_of_line);
+                match conditions.directive(rest_of_line) {
+                    Ok(true) => {},
+                    Ok(false) => issue!(token, log, "preprocessor command inside `{} {{ ... }}` not supported: #{}", self.category, rest_of_line),
+                    Err(err) => issue!(token, warn, "{}", err),
+                }
                 token = expect_token!();
             }
 
             let token_pos = src.position();
             match &*token {
                 "}" => break 'struct_,
+                ";" => continue 'struct_, // e.g. after `void method() {};`
+                "template" => {
+                    // template < ... > member
+                    let mut angles = 0;
+                    loop {
+                        match &*expect_token!() {
+                            "<" => angles += 1,
+                            ">" => { angles -= 1; if angles <= 0 { break } },
+                            ">>" => { angles -= 2; if angles <= 0 { break } },
+                            _ => {},
+                        }
+                    }
+                    continue 'struct_
+                },
+                "using" | "typedef" | "static_assert" => {
+                    // not a data member
+                    while expect_token!() != ";" {}
+                    continue 'struct_
+                },
+                "_ANONYMOUS_STRUCT" | "_ANONYMOUS_UNION" | "__C89_NAMELESS" => continue 'struct_, // e.g. `_ANONYMOUS_UNION union { ... } DUMMYUNIONNAME;`
                 "public" | "protected" | "private" => {
                     let colon = expect_token!();
                     if colon == ":" {
@@ -218,8 +267,9 @@ impl AggregateData {
                     };
 
                     if semi == ";" {
-                        let name = field_name.as_ref().map_or(Ident::from(""), |field_name| Ident::own(&**field_name));
-                        self.fields.insert(name.clone(), Field::new_enum(enum_, name));
+                        let mut field = Field::new_enum(enum_, "");
+                        field.defined_at = Some(src.token_to_location(token));
+                        self.add_field(field, field_name.as_deref(), "enum", conditions.current());
                         continue 'struct_
                     } else {
                         let field_name = field_name.as_ref().unwrap();
@@ -257,6 +307,7 @@ impl AggregateData {
                         // or:      struct {
                         let mut agg = AggregateData::default();
                         agg.category = AggregateCategory::from_str(&*token).unwrap();
+                        agg.pack = self.pack;
                         let _ = agg.add_from_cpp(agg_start, src);
 
                         // field_name ;
@@ -268,8 +319,9 @@ impl AggregateData {
                         };
 
                         if semi == ";" {
-                            let name = field_name.as_ref().map_or(Ident::from(""), |field_name| Ident::own(&**field_name));
-                            self.fields.insert(name.clone(), Field::new_agg(agg, name));
+                            let mut field = Field::new_agg(agg, "");
+                            field.defined_at = Some(src.token_to_location(token));
+                            self.add_field(field, field_name.as_deref(), &token, conditions.current());
                             continue 'struct_
                         } else {
                             let field_name = field_name.as_ref().unwrap();
@@ -289,19 +341,154 @@ impl AggregateData {
             loop {
                 let token = expect_token!();
                 match &*token {
+                    "(" if braces == 0 && !is_annotation(&possible_name) => {
+                        let fn_ptr_pos = src.position();
+                        let t1 = expect_token!();
+                        let t2 = expect_token!();
+                        if t1 == "*" || (t2 == "*" && CALLING_CONVENTIONS.contains(&&*t1)) {
+                            // parse:   ret ( cc * name ) ( params ) ;
+                            let name = if t1 == "*" { t2 } else { expect_token!() };
+                            if expect_token!() != ")" || expect_token!() != "(" {
+                                issue!(name, warn, "expected `) (` after function pointer `{}`", name);
+                                while expect_token!() != ";" {}
+                                continue 'struct_
+                            }
+                            let params = skip_balanced(src, 1).ok_or_else(|| issue!(start, warn, "expected `}}` to end `{} {}` before end of file", category, start))?;
+                            if !ty.is_empty() { ty.push(' ') }
+                            ty.push_str(&possible_name);
+                            let cc = if t1 == "*" { String::new() } else { format!("{} ", t1) };
+                            let mut field = Field::new_declspec(&format!("{} ({}*)({})", ty, cc, params.join(" ")));
+                            field.defined_at = Some(src.token_to_location(name));
+                            self.add_field(field, Some(&name), "field", conditions.current());
+                            while expect_token!() != ";" {}
+                            continue 'struct_
+                        }
+                        src.set_position(fn_ptr_pos);
+                        if ty.is_empty() && possible_name != "operator" && *possible_name != *start {
+                            // e.g. `SOME_MACRO(args) UINT field;` - not a constructor of this type
+                            ty.push_str(&possible_name);
+                            possible_name = token;
+                            continue
+                        }
+
+                        // parse:   [ret] name ( params ) [const] [= default | = 0] [: init-list] ( ; | { body } )
+                        if possible_name == "operator" { // operator()(...)
+                            let _close = expect_token!();
+                            let _open = expect_token!();
+                            ty.push_str(" operator");
+                            possible_name = _close;
+                        }
+                        let mut tokens = ty.split_ascii_whitespace().collect::<Vec<_>>();
+                        tokens.push(&possible_name);
+                        let friend = tokens.contains(&"friend");
+                        tokens.retain(|t| !["explicit", "inline", "__inline", "__forceinline", "FORCEINLINE", "constexpr", "virtual", "static", "friend"].contains(t));
+                        let (kind, name) = if let Some(op) = tokens.iter().position(|t| *t == "operator") {
+                            let rest = &tokens[op+1..];
+                            let name = if rest.iter().all(|t| !t.starts_with(|ch: char| ch.is_ascii_word_character())) { format!("operator{}", rest.concat()) } else { format!("operator {}", rest.join(" ")) };
+                            (MethodKind::Operator, name)
+                        } else if tokens.len() >= 2 && tokens[tokens.len()-2] == "~" {
+                            (MethodKind::Destructor, format!("~{}", possible_name))
+                        } else if tokens.len() == 1 {
+                            (MethodKind::Constructor, possible_name.to_string())
+                        } else {
+                            (MethodKind::Method, possible_name.to_string())
+                        };
+
+                        skip_balanced(src, 1).ok_or_else(|| issue!(start, warn, "expected `}}` to end `{} {}` before end of file", category, start))?;
+                        let mut init_list = false;
+                        let mut prev = String::new();
+                        loop {
+                            let token = expect_token!();
+                            match &*token {
+                                ";" => break,
+                                ":" => init_list = true,
+                                "(" => { skip_balanced(src, 1); },
+                                "{" if init_list && (prev.starts_with(|ch: char| ch.is_ascii_word_character()) || prev == ">") => { skip_balanced(src, 1); }, // brace initializer
+                                "{" => { skip_balanced(src, 1); break },
+                                _ => {},
+                            }
+                            prev = token.to_string();
+                        }
+
+                        if !friend {
+                            let mut m = Method::new(Ident::from(""), Ident::from(name));
+                            m.kind = kind;
+                            m.f.defined_at.insert(src.token_to_location(possible_name));
+                            self.methods.push(m);
+                        }
+                        continue 'struct_
+                    },
                     ";" if braces == 0 => {
-                        let name = Ident::own(&*possible_name);
-                        self.fields.insert(name.clone(), Field::new(ty, name));
+                        // static data members aren't part of the layout
+                        if ty.split_ascii_whitespace().any(|t| t == "static" || t == "friend") { continue 'struct_ }
+
+                        // parse:   ty name = initializer;
+                        let (ty, possible_name) = match ty.split_once(" = ").or_else(|| ty.strip_suffix(" =").map(|ty| (ty, ""))) {
+                            Some((ty, _init)) => match ty.rsplit_once(' ') {
+                                Some((ty, name)) => (ty.to_string(), name.to_string()),
+                                None => (String::new(), ty.to_string()),
+                            },
+                            None => (ty, possible_name.to_string()),
+                        };
+
+                        let mut field = Field::new_declspec(&ty);
+                        field.defined_at = Some(src.token_to_location(token));
+                        self.add_field(field, Some(&possible_name), "field", conditions.current());
                         continue 'struct_
                     },
-                    ":" => {
-                        let ty = Ident::from(ty);
-                        let name = Ident::own(&*possible_name);
-                        let f = self.fields.entry(name.clone()).or_insert_with(move || Field::new(ty, name));
-                        let bits = expect_token!();
-                        f.bits = bits.parse::<u32>().map_or(None, NonZeroU32::new);
-                        if f.bits.is_none() { issue!(bits, warn, "{} {} contains invalid bitset `: {}`", self.category, start, bits); }
-                        while expect_token!() != ";" {}
+                    "[" if braces == 0 => {
+                        // parse:   ty name [ dim ] [ dim ] ;
+                        let mut array = vec![String::new()];
+                        loop {
+                            let token = expect_token!();
+                            match &*token {
+                                "]" => match &*expect_token!() {
+                                    "[" => array.push(String::new()),
+                                    ";" => break,
+                                    other => {
+                                        issue!(token, warn, "expected `[` or `;` after `{} {} [...]`, instead got `{}`", ty, possible_name, other);
+                                        while expect_token!() != ";" {}
+                                        break
+                                    },
+                                },
+                                t => {
+                                    let dim = array.last_mut().unwrap();
+                                    if !dim.is_empty() { dim.push(' ') }
+                                    dim.push_str(t);
+                                },
+                            }
+                        }
+
+                        let mut field = Field::new_declspec(&ty);
+                        field.defined_at = Some(src.token_to_location(possible_name));
+                        field.array = array;
+                        self.add_field(field, Some(&possible_name), "array", conditions.current());
+                        continue 'struct_
+                    },
+                    ":" if braces == 0 => {
+                        // parse:   ty name : width;
+                        // or:      ty      : width;
+                        let (ty, name) = if ty.is_empty() || is_type_keyword(&possible_name) {
+                            if !ty.is_empty() { ty.push(' ') }
+                            ty.push_str(&possible_name); // e.g. `unsigned int : 4;`
+                            (ty, None)
+                        } else {
+                            (ty, Some(possible_name))
+                        };
+                        let mut bits_expr = String::new();
+                        loop {
+                            let token = expect_token!();
+                            if token == ";" { break }
+                            if !bits_expr.is_empty() { bits_expr.push(' ') }
+                            bits_expr.push_str(&token);
+                        }
+                        if bits_expr.is_empty() { issue!(token, warn, "{} {} contains bitfield `{}` without a width", self.category, start, name.as_deref().unwrap_or("")); }
+
+                        let mut field = Field::new_declspec(&ty);
+                        field.defined_at = Some(src.token_to_location(possible_name));
+                        field.bits = bits_expr.parse_cpp_int().and_then(|bits| u32::try_from(bits).ok()).and_then(NonZeroU32::new);
+                        field.bits_expr = Some(bits_expr);
+                        self.add_field(field, name.as_deref(), "bitfield", conditions.current());
                         continue 'struct_
                     },
                     // "enum" => { ... },
@@ -332,17 +519,157 @@ impl AggregateData {
             }
         }
 
+        if !conditions.is_empty() { issue!(start, log, "unterminated `#if` inside `{} {}`", self.category, start); }
         Ok(())
     }
 }
 
+impl AggregateData {
+    /// Add `field` as `name`, or under a synthetic name such as `_anonymous_union_0` if `name` is missing or merely a `DUMMYUNIONNAME`.
+    ///
+    /// Other fields with the same name declared under different preprocessor `condition`s are kept as [`Field::variants`].
+    fn add_field(&mut self, mut field: Field, name: Option<&str>, kind: &str, condition: Option<String>) {
+        field.condition = condition;
+        match name {
+            Some(name) if !is_dummy_name(name) => field.id = Ident::own(name),
+            name => {
+                let prefix = format!("_anonymous_{}_", kind);
+                let n = self.fields.values_by_insert().filter(|f| f.anonymous && f.id.starts_with(&prefix)).count();
+                field.id = Ident::from(format!("{}{}", prefix, n));
+                field.anonymous = true;
+                field.dummy_name = name.map(Ident::own);
+            },
+        }
+        match self.fields.get_mut(&field.id) {
+            None => { self.fields.insert(field.id.clone(), field); },
+            Some(prev) if prev.condition != field.condition && prev.variants.iter().all(|v| v.condition != field.condition) => prev.variants.push(field),
+            Some(_) => if let Some(loc) = field.defined_at { self.issues.push(Issue::new(loc, format!("duplicate field `{}`", field.id))) },
+        }
+    }
+
+    /// Fields as accessed from C, with the members of anonymous `struct`s and `union`s flattened into their containing aggregate, in declaration order.
+    ///
+    /// Anonymous bitfields (padding) and anonymous `enum`s are skipped, as they have no accessible members.
+    pub fn effective_fields(&self) -> Vec<EffectiveField<'_>> {
+        let mut fields = Vec::new();
+        self.collect_effective_fields(&mut Vec::new(), &mut fields);
+        fields
+    }
+
+    fn collect_effective_fields<'s>(&'s self, via: &mut Vec<&'s Field>, fields: &mut Vec<EffectiveField<'s>>) {
+        for field in self.fields.values_by_insert() {
+            match (&field.ty, field.anonymous) {
+                (_, false) => fields.push(EffectiveField { field, via: via.clone() }),
+                (Type::Aggregate(agg), true) => {
+                    via.push(field);
+                    agg.collect_effective_fields(via, fields);
+                    via.pop();
+                },
+                (_, true) => {},
+            }
+        }
+    }
+
+    /// Describe how `other` (e.g. a redefinition of the same type in another header) differs from `self`, field by field.
+    pub(crate) fn differences(&self, other: &AggregateData) -> Vec<String> {
+        let mut diffs = Vec::new();
+        if self.category != other.category  { diffs.push(format!("`{}` vs `{}`", self.category, other.category)); }
+        if self.base != other.base          { diffs.push(format!("base {:?} vs {:?}", self.base, other.base)); }
+        if self.pack != other.pack          { diffs.push(format!("packing {:?} vs {:?}", self.pack, other.pack)); }
+        if self.align != other.align        { diffs.push(format!("alignment {:?} vs {:?}", self.align, other.align)); }
+
+        let a = self.fields.values_by_insert().collect::<Vec<_>>();
+        let b = other.fields.values_by_insert().collect::<Vec<_>>();
+        for i in 0 .. a.len().max(b.len()) {
+            match (a.get(i), b.get(i)) {
+                (Some(a), Some(b)) => {
+                    let (da, db) = (a.describe(), b.describe());
+                    if da != db { diffs.push(format!("field {} is `{}` vs `{}`", i, da, db)); }
+                    if a.condition != b.condition { diffs.push(format!("`{}` is declared under `{}` vs `{}`", a.id, a.condition.as_deref().unwrap_or("(no condition)"), b.condition.as_deref().unwrap_or("(no condition)"))); }
+                    let variants = |f: &Field| if f.variants.is_empty() { "(none)".into() } else { f.variants.iter().map(|v| format!("{} (#if {})", v.describe(), v.condition.as_deref().unwrap_or("1"))).collect::<Vec<_>>().join("; ") };
+                    let (va, vb) = (variants(a), variants(b));
+                    if va != vb { diffs.push(format!("`{}` variants are `{}` vs `{}`", a.id, va, vb)); }
+                    if let (Type::Aggregate(aa), Type::Aggregate(ba)) = (&a.ty, &b.ty) {
+                        diffs.extend(aa.differences(ba).into_iter().map(|d| format!("`{}`: {}", a.id, d)));
+                    }
+                },
+                (Some(a), None) => diffs.push(format!("field `{}` missing from redefinition", a.id)),
+                (None, Some(b)) => diffs.push(format!("field `{}` only in redefinition", b.id)),
+                (None, None)    => {},
+            }
+        }
+        diffs
+    }
+
+    /// Evaluate [`Field::bits_expr`]s and place bitfields into storage units per MSVC's rules, recursing into anonymous and nested aggregates.
+    pub(crate) fn layout_bitfields(&mut self, root: &Root) {
+        let category = self.category;
+        let mut issues = Vec::new();
+        let mut unit = None; // (storage_unit, storage_size, bits_used)
+        let mut next_unit = 0;
+
+        for field in self.fields.values_by_insert_mut() {
+            let bits_expr = match field.bits_expr.as_deref() {
+                Some(e) => e,
+                None => {
+                    unit = None;
+                    if let Type::Aggregate(agg) = &mut field.ty { agg.layout_bitfields(root); }
+                    continue
+                },
+            };
+            let loc = field.defined_at.clone().unwrap_or_default();
+
+            let bits = match root.eval(bits_expr).and_then(|bits| u32::try_from(bits).ok()) {
+                Some(bits) => bits,
+                None => {
+                    issues.push(Issue::new(loc, format!("unable to evaluate width of bitfield `{}` (`: {}`)", field.id, bits_expr)));
+                    unit = None;
+                    continue
+                },
+            };
+            field.bits = NonZeroU32::new(bits);
+
+            let ty = match &field.ty { Type::Basic(ty) => ty.as_str(), _ => "" };
+            let size = match root.integer_size(ty) {
+                Some(size) => size,
+                None => {
+                    issues.push(Issue::new(loc, format!("unable to determine storage unit size of bitfield `{}` (type `{}`)", field.id, ty)));
+                    unit = None;
+                    continue
+                },
+            };
+            if bits > size * 8 {
+                issues.push(Issue::new(loc, format!("bitfield `{}` is {} bits wide, but `{}` is only {} bits", field.id, bits, ty, size * 8)));
+                unit = None;
+                continue
+            }
+            if bits == 0 { unit = None; continue } // zero width bitfields end the current storage unit
+
+            let (storage_unit, bit_offset) = match unit {
+                Some((storage_unit, storage_size, used)) if category != AggregateCategory::Union && storage_size == size && used + bits <= size * 8 => (storage_unit, used),
+                _ => {
+                    next_unit += 1;
+                    (next_unit - 1, 0)
+                },
+            };
+            unit = Some((storage_unit, size, bit_offset + bits));
+            field.bit_layout = Some(BitfieldLayout { storage_unit, storage_size: size, bit_offset });
+        }
+
+        self.issues.extend(issues);
+    }
+}
+
 impl Debug for Aggregate {
     fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
         fmt.debug_struct("Aggregate")
             .field("id",            &self.id                                    )
             .field("category",      &self.category                              )
             .field("base",          &self.base                                  )
+            .field("pack",          &self.pack                                  )
+            .field("align",         &self.align                                 )
             .field("fields",        &self.fields.values_by_insert().collect::<Vec<_>>()   )
+            .field("methods",       &self.methods                               )
             .field("issues",        &self.issues                                )
             .finish_non_exhaustive()
     }
@@ -353,12 +680,47 @@ impl Debug for AggregateData {
         fmt.debug_struct("AggregateData")
             .field("category",      &self.category                              )
             .field("base",          &self.base                                  )
+            .field("pack",          &self.pack                                  )
+            .field("align",         &self.align                                 )
             .field("fields",        &self.fields.values_by_insert().collect::<Vec<_>>()   )
+            .field("methods",       &self.methods                               )
             .field("issues",        &self.issues                                )
             .finish_non_exhaustive()
     }
 }
 
+const CALLING_CONVENTIONS : &[&str] = &["STDMETHODCALLTYPE", "WINAPI", "APIENTRY", "CALLBACK", "__stdcall", "__cdecl", "__fastcall", "__thiscall", "__vectorcall", "STDAPICALLTYPE", "NTAPI"];
+
+/// Skip tokens until the `(`/`[`/`{` nesting `depth` reaches 0, returning the skipped tokens (excluding the final closing token.)
+fn skip_balanced(src: &mut SrcReader, mut depth: usize) -> Option<Vec<String>> {
+    let mut tokens = Vec::new();
+    while depth > 0 {
+        let token = src.next_token()?;
+        match &*token {
+            "(" | "[" | "{" => depth += 1,
+            ")" | "]" | "}" => { depth -= 1; if depth == 0 { break } },
+            _ => {},
+        }
+        tokens.push(token.to_string());
+    }
+    Some(tokens)
+}
+
+/// `DECLSPEC_ALIGN(16)`, `_Field_size_(n)`, etc. - macros that take arguments within a field's type.
+fn is_annotation(name: &str) -> bool {
+    name.starts_with('_') || name.starts_with("DECLSPEC_")
+}
+
+/// Keywords that can end a multi-word type (e.g. the `int` of `unsigned int`), and so can't be a field or parameter name.
+pub(crate) fn is_type_keyword(name: &str) -> bool {
+    ["void", "char", "short", "int", "long", "signed", "unsigned", "bool", "float", "double", "const", "volatile", "__int8", "__int16", "__int32", "__int64"].contains(&name)
+}
+
+fn is_dummy_name(name: &str) -> bool {
+    let digits = name.trim_end_matches(|ch: char| ch.is_ascii_digit());
+    digits == "DUMMYUNIONNAME" || digits == "DUMMYSTRUCTNAME"
+}
+
 impl Deref for Aggregate {
     type Target = AggregateData;
     fn deref(&self) -> &Self::Target { &self.data }
@@ -367,3 +729,140 @@ impl Deref for Aggregate {
 impl DerefMut for Aggregate {
     fn deref_mut(&mut self) -> &mut Self::Target { &mut self.data }
 }
+
+
+
+#[test] fn test_anonymous_fields() {
+    let root = Root::from_cpp_src(r#"
+        typedef struct _PROPVARIANT_LIKE {
+            union {
+                struct {
+                    WORD vt;
+                    WORD wReserved1;
+                } DUMMYSTRUCTNAME;
+                DECIMAL decVal;
+            } DUMMYUNIONNAME;
+            union {
+                LONG lVal;
+                struct { UINT a : 4; UINT : 4; UINT b : 8; };
+            };
+            UINT : 0;
+            struct { WORD x; } named;
+        } PROPVARIANT_LIKE;
+    "#);
+
+    let s = root.structs.get("PROPVARIANT_LIKE").unwrap();
+    let ids = s.fields.values_by_insert().map(|f| (f.id.as_str(), f.anonymous, f.dummy_name.as_ref().map(|d| d.as_str()))).collect::<Vec<_>>();
+    assert_eq!(ids, [
+        ("_anonymous_union_0",      true,   Some("DUMMYUNIONNAME")),
+        ("_anonymous_union_1",      true,   None),
+        ("_anonymous_bitfield_0",   true,   None),
+        ("named",                   false,  None),
+    ]);
+
+    let effective = s.effective_fields().iter().map(|f| {
+        let via = f.via.iter().map(|v| v.id.as_str()).collect::<Vec<_>>().join(".");
+        format!("{}:{}", via, f.field.id)
+    }).collect::<Vec<_>>();
+    assert_eq!(effective, [
+        "_anonymous_union_0._anonymous_struct_0:vt",
+        "_anonymous_union_0._anonymous_struct_0:wReserved1",
+        "_anonymous_union_0:decVal",
+        "_anonymous_union_1:lVal",
+        "_anonymous_union_1._anonymous_struct_0:a",
+        "_anonymous_union_1._anonymous_struct_0:b",
+        ":named",
+    ]);
+}
+
+
+#[test] fn test_conditional_fields() {
+    let root = Root::from_cpp_src(r#"
+        typedef struct _D3DCAPS {
+            DWORD DevCaps;
+        #if(DIRECT3D_VERSION >= 0x0900)
+            DWORD MaxNpatchTessellationLevel;
+            DWORD Reserved5;
+        #else
+            float Reserved5;
+        #endif
+            DWORD VS20Caps;
+        } D3DCAPS;
+    "#);
+
+    let s = root.structs.get("D3DCAPS").unwrap();
+    assert!(s.issues.is_empty(), "{:?}", s.issues);
+    let fields = s.fields.values_by_insert().map(|f| (f.id.as_str(), f.condition.as_deref(), f.variants.len())).collect::<Vec<_>>();
+    assert_eq!(fields, [
+        ("DevCaps",                     None,                                   0),
+        ("MaxNpatchTessellationLevel",  Some("(DIRECT3D_VERSION >= 0x0900)"),   0),
+        ("Reserved5",                   Some("(DIRECT3D_VERSION >= 0x0900)"),   1),
+        ("VS20Caps",                    None,                                   0),
+    ]);
+    assert_eq!(s.fields.get("Reserved5").unwrap().variants[0].condition.as_deref(), Some("!(DIRECT3D_VERSION >= 0x0900)"));
+
+    let root = Root::from_cpp_src(r#"
+        typedef struct DUPLICATES {
+        #ifdef FOO
+            DWORD A;
+        #else
+            float A;
+        #endif
+        #ifndef FOO
+            BYTE A;
+        #endif
+        } DUPLICATES;
+    "#);
+    let s = root.structs.get("DUPLICATES").unwrap();
+    assert_eq!(s.issues.iter().map(|i| i.message.as_str()).collect::<Vec<_>>(), ["duplicate field `A`"]);
+    let a = s.fields.get("A").unwrap();
+    assert_eq!(a.condition.as_deref(), Some("defined(FOO)"));
+    assert_eq!(a.variants.len(), 1);
+    assert!(matches!(&a.variants[0].ty, Type::Basic(ty) if ty.as_str() == "float"));
+}
+
+
+#[test] fn test_methods_and_bases() {
+    let root = Root::from_cpp_src(r#"
+        typedef struct D3D12_RECT { LONG left; LONG top; LONG right; LONG bottom; } D3D12_RECT;
+
+        struct CD3DX12_RECT : public D3D12_RECT
+        {
+            CD3DX12_RECT() = default;
+            explicit CD3DX12_RECT( const D3D12_RECT& o ) noexcept : D3D12_RECT(o) {}
+            explicit CD3DX12_RECT( LONG Left, LONG Top ) noexcept : D3D12_RECT{ Left, Top, 0, 0 }, extra{} { left = Left; }
+            ~CD3DX12_RECT() {}
+            operator const D3D12_RECT&() const noexcept { return *this; }
+            bool operator==(const CD3DX12_RECT& r) const { return left == r.left; };
+            template <typename T> T get() const { return T(); }
+            static inline UINT Count() { return 4; }
+            static const UINT Max = 4;
+            using Base = D3D12_RECT;
+            UINT extra = 0;
+            HRESULT (STDMETHODCALLTYPE *Callback)(void *pContext, UINT n);
+        };
+    "#);
+
+    let rect = root.structs.get("CD3DX12_RECT").unwrap();
+    assert_eq!(rect.base.as_ref().map(|b| b.as_str()), Some("D3D12_RECT"));
+    assert!(rect.issues.is_empty(), "{:?}", rect.issues);
+
+    let fields = rect.fields.values_by_insert().map(|f| f.describe()).collect::<Vec<_>>();
+    assert_eq!(fields, ["UINT extra", "HRESULT (STDMETHODCALLTYPE *)(void * pContext , UINT n) Callback"]);
+
+    let methods = rect.methods.iter().map(|m| (m.kind, m.f.id.as_str(), m.ty.as_str())).collect::<Vec<_>>();
+    use MethodKind::*;
+    assert_eq!(methods, [
+        (Constructor,   "CD3DX12_RECT",                 "CD3DX12_RECT"),
+        (Constructor,   "CD3DX12_RECT",                 "CD3DX12_RECT"),
+        (Constructor,   "CD3DX12_RECT",                 "CD3DX12_RECT"),
+        (Destructor,    "~CD3DX12_RECT",                "CD3DX12_RECT"),
+        (Operator,      "operator const D3D12_RECT &",  "CD3DX12_RECT"),
+        (Operator,      "operator==",                   "CD3DX12_RECT"),
+        (Method,        "get",                          "CD3DX12_RECT"),
+        (Method,        "Count",                        "CD3DX12_RECT"),
+    ]);
+
+    let layout = root.layout("CD3DX12_RECT", Arch::X64).unwrap();
+    assert_eq!((layout.size, layout.offset_of("extra"), layout.offset_of("Callback")), (32, Some(16), Some(24)));
+}
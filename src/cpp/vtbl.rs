@@ -0,0 +1,273 @@
+use crate::*;
+
+use std::collections::BTreeSet;
+use std::fmt::{self, Debug, Formatter};
+
+
+
+/// `typedef struct IFooVtbl { BEGIN_INTERFACE ... END_INTERFACE } IFooVtbl;` - the C view of an interface's vtable.
+///
+/// Unlike C++ interface definitions, these list every slot in order, including inherited ones.
+pub struct Vtbl {
+    /// Location(s) this vtable was defined at.
+    pub defined_at:             BTreeSet<Location>,
+
+    /// The vtable struct name, such as `IUnknownVtbl`.
+    pub id:                     Ident,
+
+    /// The interface this is a vtable for, such as `IUnknown`.
+    pub interface:              Ident,
+
+    /// The function pointers of this vtable, in slot order.
+    /// Only the first branch of each `#if` / `#elif` / `#else` is included.
+    pub slots:                  Vec<VtblSlot>,
+
+    /// The function pointers declared in other `#elif` / `#else` branches, in declaration order.
+    pub variants:               Vec<VtblSlot>,
+
+    pub(crate) _non_exhaustive: (),
+}
+
+/// `HRESULT ( STDMETHODCALLTYPE *QueryInterface )( IUnknown * This, ... );`
+pub struct VtblSlot {
+    /// The method name, such as `QueryInterface`.
+    pub id:                     Ident,
+
+    /// The interface declaring this method, if known via `DECLSPEC_XFGVIRT(IUnknown, QueryInterface)`.
+    pub declared_by:            Option<Ident>,
+
+    pub defined_at:             Location,
+
+    /// The preprocessor condition this slot was declared under, relative to the enclosing `struct { ... }` (e.g. `defined(_MSC_VER) || !defined(_WIN32)`)
+    pub condition:              Option<String>,
+
+    pub(crate) _non_exhaustive: (),
+}
+
+impl Debug for Vtbl {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("Vtbl")
+            .field("id",            &self.id                        )
+            .field("interface",     &self.interface                 )
+            .field("slots",         &self.slots.iter().map(|s| s.id.as_str()).collect::<Vec<_>>())
+            .field("variants",      &self.variants.iter().map(|s| s.id.as_str()).collect::<Vec<_>>())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Debug for VtblSlot {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("VtblSlot")
+            .field("id",            &self.id            )
+            .field("declared_by",   &self.declared_by   )
+            .field("condition",     &self.condition     )
+            .finish_non_exhaustive()
+    }
+}
+
+
+
+impl Vtbl {
+    pub fn valid_name(name: &str) -> bool { valid_name(name) && name.len() > "Vtbl".len() && name.ends_with("Vtbl") }
+
+    pub fn new(id: Ident) -> Self {
+        let interface = Ident::own(id.strip_suffix("Vtbl").unwrap_or(&id));
+        Self { defined_at: Default::default(), id, interface, slots: Vec::new(), variants: Vec::new(), _non_exhaustive: () }
+    }
+
+    /// Parse e.g. `BEGIN_INTERFACE ... END_INTERFACE }`
+    ///
+    /// Expects `typedef struct IFooVtbl {` to have already been parsed.
+    pub(crate) fn add_from_cpp(&mut self, src: &mut SrcReader) -> Result<(), ()> {
+        let mut declared_by = None;
+        let mut conditions = Conditions::default();
+        while let Some(token) = src.next_token() {
+            match &*token {
+                "}" => return Ok(()),
+                "#" => {
+                    // #if defined(_MSC_VER) || !defined(_WIN32)
+                    let line = src.next_line();
+                    if conditions.directive(line.as_ref().map_or("", |l| &**l)).is_err() { return Err(()) }
+                },
+                "BEGIN_INTERFACE" | "END_INTERFACE" | ";" => {},
+                "DECLSPEC_XFGVIRT" => {
+                    // DECLSPEC_XFGVIRT(IUnknown, QueryInterface)
+                    let args = [src.next_token(), src.next_token(), src.next_token(), src.next_token(), src.next_token()];
+                    if let [Some(o), Some(interface), Some(comma), Some(_method), Some(c)] = args {
+                        if o == "(" && comma == "," && c == ")" { declared_by = Some(Ident::own(&interface)); }
+                    }
+                },
+                "(" => {
+                    // HRESULT ( STDMETHODCALLTYPE *QueryInterface )( IUnknown * This, ... );
+                    let mut name = None;
+                    while let Some(token) = src.next_token() {
+                        match &*token {
+                            ")" => break,
+                            "*" => name = src.next_token(),
+                            _   => {},
+                        }
+                    }
+                    let name = if let Some(name) = name { name } else { return Err(()) };
+                    let mut depth = 0;
+                    while let Some(token) = src.next_token() {
+                        match &*token {
+                            "(" => depth += 1,
+                            ")" => depth -= 1,
+                            ";" if depth == 0 => break,
+                            _   => {},
+                        }
+                    }
+                    let slot = VtblSlot {
+                        id:                 Ident::own(&name),
+                        declared_by:        declared_by.take(),
+                        defined_at:         src.token_to_location(name),
+                        condition:          conditions.current(),
+                        _non_exhaustive:    (),
+                    };
+                    if conditions.in_first_branch() { self.slots.push(slot) } else { self.variants.push(slot) }
+                },
+                _ => {}, // return types, SAL, etc.
+            }
+        }
+        Err(())
+    }
+}
+
+
+
+#[test] fn test_vtbls() {
+    let root = Root::from_cpp_src(r#"
+        MIDL_INTERFACE("00000000-0000-0000-C000-000000000046")
+        IUnknown
+        {
+        public:
+            virtual HRESULT STDMETHODCALLTYPE QueryInterface(REFIID riid, void **ppvObject) = 0;
+            virtual ULONG STDMETHODCALLTYPE AddRef( void) = 0;
+            virtual ULONG STDMETHODCALLTYPE Release( void) = 0;
+        };
+
+        MIDL_INTERFACE("00000000-0000-0000-0000-000000000001")
+        IGood : public IUnknown
+        {
+        public:
+            virtual HRESULT STDMETHODCALLTYPE First( void) = 0;
+            virtual HRESULT STDMETHODCALLTYPE Second( void) = 0;
+        };
+
+        MIDL_INTERFACE("00000000-0000-0000-0000-000000000002")
+        IBad : public IUnknown
+        {
+        public:
+            virtual HRESULT STDMETHODCALLTYPE First( void) = 0;
+            virtual HRESULT STDMETHODCALLTYPE Second( void) = 0;
+            virtual HRESULT STDMETHODCALLTYPE Third( void) = 0;
+        };
+
+        typedef struct IGoodVtbl
+        {
+            BEGIN_INTERFACE
+
+            DECLSPEC_XFGVIRT(IUnknown, QueryInterface)
+            HRESULT ( STDMETHODCALLTYPE *QueryInterface )(
+                IGood * This,
+                /* [in] */ REFIID riid,
+                /* [annotation][iid_is][out] */
+                _COM_Outptr_  void **ppvObject);
+
+            DECLSPEC_XFGVIRT(IUnknown, AddRef)
+            ULONG ( STDMETHODCALLTYPE *AddRef )(
+                IGood * This);
+
+            DECLSPEC_XFGVIRT(IUnknown, Release)
+            ULONG ( STDMETHODCALLTYPE *Release )(
+                IGood * This);
+
+            DECLSPEC_XFGVIRT(IGood, First)
+            HRESULT ( STDMETHODCALLTYPE *First )(
+                IGood * This);
+
+            HRESULT ( STDMETHODCALLTYPE *Second )(
+                IGood * This);
+
+            END_INTERFACE
+        } IGoodVtbl;
+
+        typedef struct IBadVtbl
+        {
+            BEGIN_INTERFACE
+            DECLSPEC_XFGVIRT(IBad, QueryInterface)
+            HRESULT ( STDMETHODCALLTYPE *QueryInterface )( IBad * This, REFIID riid, void **ppvObject);
+            ULONG ( STDMETHODCALLTYPE *AddRef )( IBad * This);
+            ULONG ( STDMETHODCALLTYPE *Release )( IBad * This);
+            HRESULT ( STDMETHODCALLTYPE *Second )( IBad * This);
+            HRESULT ( STDMETHODCALLTYPE *First )( IBad * This);
+            END_INTERFACE
+        } IBadVtbl;
+
+        typedef struct ICOnlyVtbl
+        {
+            BEGIN_INTERFACE
+            HRESULT ( STDMETHODCALLTYPE *Foo )( ICOnly * This);
+            END_INTERFACE
+        } ICOnlyVtbl;
+
+        MIDL_INTERFACE("00000000-0000-0000-0000-000000000003")
+        IConditional : public IUnknown
+        {
+        public:
+        #if defined(_MSC_VER) || !defined(_WIN32)
+            virtual D3D12_HEAP_DESC STDMETHODCALLTYPE GetDesc( void) = 0;
+        #else
+            virtual D3D12_HEAP_DESC *STDMETHODCALLTYPE GetDesc(
+                D3D12_HEAP_DESC * RetVal) = 0;
+        #endif
+        };
+
+        typedef struct IConditionalVtbl
+        {
+            BEGIN_INTERFACE
+            HRESULT ( STDMETHODCALLTYPE *QueryInterface )( IConditional * This, REFIID riid, void **ppvObject);
+            ULONG ( STDMETHODCALLTYPE *AddRef )( IConditional * This);
+            ULONG ( STDMETHODCALLTYPE *Release )( IConditional * This);
+        #if !defined(_WIN32)
+            D3D12_HEAP_DESC ( STDMETHODCALLTYPE *GetDesc )( IConditional * This);
+        #else
+            D3D12_HEAP_DESC *( STDMETHODCALLTYPE *GetDesc )( IConditional * This, D3D12_HEAP_DESC * RetVal);
+        #endif
+            END_INTERFACE
+        } IConditionalVtbl;
+
+        typedef struct IBrokenVtbl
+        {
+            BEGIN_INTERFACE
+            ULONG ( STDMETHODCALLTYPE Broken )( IBroken * This);
+    "#);
+
+    let good = root.vtbls.get("IGoodVtbl").unwrap();
+    assert_eq!(good.interface.as_str(), "IGood");
+    let slots = good.slots.iter().map(|s| (s.id.as_str(), s.declared_by.as_ref().map(|d| d.as_str()))).collect::<Vec<_>>();
+    assert_eq!(slots, [
+        ("QueryInterface",  Some("IUnknown")),
+        ("AddRef",          Some("IUnknown")),
+        ("Release",         Some("IUnknown")),
+        ("First",           Some("IGood")),
+        ("Second",          None),
+    ]);
+    assert!(root.interfaces.get("IGood").unwrap().issues.is_empty());
+
+    assert_eq!(root.vtbls.get("ICOnlyVtbl").unwrap().slots.len(), 1);
+
+    let conditional = root.vtbls.get("IConditionalVtbl").unwrap();
+    assert_eq!(conditional.slots.iter().map(|s| s.id.as_str()).collect::<Vec<_>>(), ["QueryInterface", "AddRef", "Release", "GetDesc"]);
+    assert_eq!(conditional.slots[3].condition.as_deref(), Some("!defined(_WIN32)"));
+    assert_eq!(conditional.variants.iter().map(|s| (s.id.as_str(), s.condition.as_deref())).collect::<Vec<_>>(), [("GetDesc", Some("defined(_WIN32)"))]);
+    assert!(root.interfaces.get("IConditional").unwrap().issues.is_empty());
+    assert!(root.vtbls.get("IBrokenVtbl").is_none());
+
+    let bad = root.interfaces.get("IBad").unwrap().issues.iter().map(|i| i.message.as_str()).collect::<Vec<_>>();
+    assert_eq!(bad, [
+        "C `IBadVtbl` has 5 slots, but C++ `IBad` has 6 methods",
+        "C `IBadVtbl` slot 3 is `Second`, but C++ `IBad` has `First`",
+        "C `IBadVtbl` slot 0 `QueryInterface` is declared by `IBad`, but C++ declares it in `IUnknown`",
+    ]);
+}
@@ -44,6 +44,54 @@ pub(crate) trait StrExt {
         }
     }
 
+    /// Parse a C/C++ integer literal such as `42`, `0x8000FFFFL`, `010`, or `0b101ull`
+    fn parse_cpp_int(&self) -> Option<u64> {
+        let s = self.as_str().trim().replace('\'', "");
+        let (digits, radix) = if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            (hex.trim_end_matches(|ch: char| "uUlL".contains(ch)), 16)
+        } else if let Some(bin) = s.strip_prefix("0b").or_else(|| s.strip_prefix("0B")) {
+            (bin.trim_end_matches(|ch: char| "uUlL".contains(ch)), 2)
+        } else {
+            let dec = s.trim_end_matches("i64").trim_end_matches("i32").trim_end_matches(|ch: char| "uUlL".contains(ch));
+            if dec.len() > 1 && dec.starts_with('0') { (&dec[1..], 8) } else { (dec, 10) }
+        };
+        if digits.is_empty() { return None }
+        u64::from_str_radix(digits, radix).ok()
+    }
+
+    /// Remove `// ...` and `/* ... */` comments, outside of string and character literals.
+    fn without_cpp_comments(&self) -> String {
+        let mut s = self.as_str();
+        let mut out = String::with_capacity(s.len());
+        while let Some(i) = s.find(|ch| "/\"\'".contains(ch)) {
+            let (before, after) = s.split_at(i);
+            out.push_str(before);
+            if after.starts_with("//") {
+                return out;
+            } else if let Some(comment) = after.strip_prefix("/*") {
+                out.push(' ');
+                s = comment.split_once("*/").map_or("", |(_, after)| after);
+            } else if let Some(after) = after.strip_prefix('/') {
+                out.push('/');
+                s = after;
+            } else {
+                // string or character literal
+                let quote = after.chars().next().unwrap();
+                let mut end = 1;
+                let bytes = after.as_bytes();
+                while end < bytes.len() && bytes[end] as char != quote {
+                    if bytes[end] == b'\\' { end += 1 }
+                    end += 1;
+                }
+                let end = (end + 1).min(after.len());
+                out.push_str(&after[..end]);
+                s = &after[end..];
+            }
+        }
+        out.push_str(s);
+        out
+    }
+
     fn strip_prefix_suffix  (&self, prefix: &str, suffix: &str) -> Option<&str> { self.as_str().strip_prefix(prefix).and_then(move |s| s.strip_suffix(suffix)) }
     fn split_once_trim      (&self, s: &str)            -> Option<(&str, &str)> { self.as_str().split_once(s).map(|(a, b)| (a.trim_end(), b.trim_start())) }
     fn rsplit_once_trim     (&self, s: &str)            -> Option<(&str, &str)> { self.as_str().rsplit_once(s).map(|(a, b)| (a.trim_end(), b.trim_start())) }
@@ -80,6 +128,27 @@ impl StrExt for str {
     }
 }
 
+#[test] fn test_parse_cpp_int() {
+    assert_eq!(Some(0),             "0"             .parse_cpp_int());
+    assert_eq!(Some(42),            "42"            .parse_cpp_int());
+    assert_eq!(Some(8),             "010"           .parse_cpp_int());
+    assert_eq!(Some(5),             "0b101ull"      .parse_cpp_int());
+    assert_eq!(Some(0x8000FFFF),    "0x8000FFFFL"   .parse_cpp_int());
+    assert_eq!(Some(1_000_000),     "1'000'000"     .parse_cpp_int());
+    assert_eq!(Some(7),             "7i64"          .parse_cpp_int());
+    assert_eq!(None,                "0x"            .parse_cpp_int());
+    assert_eq!(None,                "FOO"           .parse_cpp_int());
+    assert_eq!(None,                "1.0f"          .parse_cpp_int());
+}
+
+#[test] fn test_without_cpp_comments() {
+    assert_eq!("FOO 1 ",            "FOO 1 // comment"          .without_cpp_comments());
+    assert_eq!("FOO   1",           "FOO /* a */ 1"             .without_cpp_comments());
+    assert_eq!("FOO \"//\" '/'",    "FOO \"//\" '/'"            .without_cpp_comments());
+    assert_eq!("FOO \"\\\"//\"",   "FOO \"\\\"//\"// c"         .without_cpp_comments());
+    assert_eq!("1 / 2",             "1 / 2"                     .without_cpp_comments());
+}
+
 #[test] fn test_find_token() {
     assert_eq!(Some(0),  "FOO" .find_token("FOO"));
     assert_eq!(Some(0),  "FOO ".find_token("FOO"));
@@ -52,6 +52,8 @@ impl RootBuilder {
         for header in [
             // misc
             r"shared\guiddef.h",
+            r"shared\ntstatus.h",
+            r"shared\winerror.h",
             r"um\unknwnbase.h",
             r"um\winuser.h",
 
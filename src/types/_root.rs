@@ -10,7 +10,7 @@ use std::sync::*;
 
 
 
-/// { interfaces, structs, flags, enums, macros, constants, ... }
+/// { interfaces, structs, flags, enums, macros, constants, errors, ... }
 #[derive(Default)]
 pub struct Root {
     pub interfaces:             VecMap<Ident, Interface>,
@@ -23,9 +23,27 @@ pub struct Root {
     pub constants:              VecMap<Ident, Constant>,
     pub namespaces:             VecMap<Ident, Namespace>,
     pub functions:              VecMap<Ident, Function>,
+    pub errors:                 VecMap<Ident, ErrorCode>,
+    pub static_asserts:         Vec<StaticAssert>,
+    pub vtbls:                  VecMap<Ident, Vtbl>,
+    pub(crate) pending_iids:    Vec<PendingIid>,
+    pub(crate) pending_error_messages: HashMap<Ident, String>,
+    pub(crate) pending_flag_operators: Vec<(Location, Ident)>,
+    pub(crate) pending_interface_redefinitions: Vec<(Location, Interface)>,
+    pub(crate) enumerators:     HashMap<Ident, i64>,
+    pub(crate) typedefs:        HashMap<Ident, String>,
     pub(crate) _non_exhaustive: (),
 }
 
+/// An IID associated with an interface outside of it's definition, such as `DEFINE_GUID(IID_IFoo, ...)` or `interface DECLSPEC_UUID("...") IFoo;`.
+/// Resolved against [`Root::interfaces`] during cleanup, as the interface might not have been defined yet.
+pub(crate) struct PendingIid {
+    location:   Location,
+    interface:  Ident,
+    iid:        Option<Guid>,
+    constant:   Option<Ident>,
+}
+
 impl Debug for Root {
     fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
         fmt.debug_struct("Root")
@@ -39,6 +57,9 @@ impl Debug for Root {
             .field("constants",     &self.constants     .values_by_key().collect::<Vec<_>>())
             .field("namespaces",    &self.namespaces    .values_by_key().collect::<Vec<_>>())
             .field("functions",     &self.functions     .values_by_key().collect::<Vec<_>>())
+            .field("errors",        &self.errors        .values_by_key().collect::<Vec<_>>())
+            .field("static_asserts",&self.static_asserts                                       )
+            .field("vtbls",         &self.vtbls         .values_by_key().collect::<Vec<_>>())
             .finish_non_exhaustive()
     }
 }
@@ -50,54 +71,304 @@ impl Root {
 
     /// Mark inherited methods etc.
     pub(crate) fn cleanup(&mut self) {
+        self.cleanup_iids();
+        self.cleanup_enums();
+        self.cleanup_flags();
+        self.cleanup_bitfields();
+        self.cleanup_static_asserts();
+        self.cleanup_errors();
         self.cleanup_inherited_methods();
+        self.cleanup_interface_redefinitions();
+        self.cleanup_interface_kinds();
+        self.cleanup_vtbls();
         self.cleanup_macro_methods();
     }
 
+    /// Evaluate a C/C++ integer constant expression such as `MAKE_HRESULT(SEVERITY_ERROR, FACILITY_WIN32, 5)` in terms of this root's constants and macros.
+    pub fn eval(&self, expr: &str) -> Option<i64> { eval_expr(expr, self) }
+
     #[inline] pub(crate) fn add_from_cpp_path(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
         self.impl_add_from_cpp_path(path.as_ref())
     }
 
+    fn cleanup_iids(&mut self) {
+        for pending in std::mem::take(&mut self.pending_iids).into_iter() {
+            let loc = &pending.location;
+            let interface = if let Some(i) = self.interfaces.get_mut(&pending.interface) { i } else { continue }; // e.g. CLSIDs
+            if let Some(constant) = pending.constant {
+                interface.iid_constant.get_or_insert(constant);
+            }
+            match (interface.iid, pending.iid) {
+                (_, None) => {},
+                (None, Some(iid)) => interface.iid = Some(iid),
+                (Some(prev), Some(iid)) if prev == iid => {},
+                (Some(prev), Some(iid)) => warning!(at: &loc.path, line: loc.line_no_or_0(), column: loc.col_no_or_0(), "interface `{}` has IID {} here, but previously had IID {}", interface.id, iid, prev),
+            }
+        }
+    }
+
+    fn cleanup_enums(&mut self) {
+        let mut enums = std::mem::take(&mut self.enums);
+        for e in enums.values_by_insert_mut() {
+            // implicit values follow on from the previous enumerator in the same `#if` branch, which may be a variant
+            let mut decls = e.data.values.values_by_insert().flat_map(|e| std::iter::once(e).chain(e.variants.iter())).collect::<Vec<_>>();
+            decls.sort_by_key(|e| e.index);
+            let mut evaluated = BTreeMap::<usize, Option<i64>>::new();
+            for decl in decls {
+                let value = match decl.value.as_deref() {
+                    Some(value) => self.eval(value),
+                    None        => decl.follows.map_or(Some(0), |prev| evaluated.get(&prev).copied().flatten().and_then(|v| v.checked_add(1))),
+                };
+                evaluated.insert(decl.index, value);
+                let main = e.data.values.get(&decl.id).is_some_and(|e| e.index == decl.index);
+                if let Some(value) = value.filter(|_| main) { self.enumerators.insert(decl.id.clone(), value); }
+            }
+
+            for enumerator in e.data.values.values_by_insert_mut() {
+                for variant in enumerator.variants.iter_mut() { variant.evaluated = evaluated[&variant.index]; }
+                enumerator.evaluated = evaluated[&enumerator.index];
+            }
+            e.data.classify_enumerators();
+        }
+        self.enums = enums;
+    }
+
+    fn cleanup_flags(&mut self) {
+        let mut operators = BTreeMap::<Ident, BTreeSet<Location>>::new();
+        for (loc, id) in std::mem::take(&mut self.pending_flag_operators).into_iter() {
+            operators.entry(id).or_default().insert(loc);
+        }
+
+        for e in self.enums.values_by_insert() {
+            let values = e.values.values_by_insert().filter(|v| v.kind != EnumeratorKind::ForceSize).map(|v| v.evaluated).collect::<Option<Vec<_>>>();
+            let guess = values.is_some_and(|values| {
+                let bits = values.iter().filter(|v| **v > 0 && v.count_ones() == 1).count();
+                looks_like_flags(values.into_iter()) && (e.id.contains("FLAG") || bits >= 3)
+            });
+            let operators_at = operators.remove(&e.id).or_else(|| operators.remove(format!("_{}", e.id).as_str())).unwrap_or_default();
+            if operators_at.is_empty() && !guess { continue }
+
+            if let Some(mut flags) = Flags::from_enum(e.id.clone(), e) {
+                flags.operators_at = operators_at;
+                self.flags.insert(flags.id.clone(), flags);
+            } else if let Some(loc) = operators_at.iter().next() {
+                warning!(at: &loc.path, line: loc.line_no_or_0(), column: loc.col_no_or_0(), "unable to evaluate all enumerators of `{}` for `DEFINE_ENUM_FLAG_OPERATORS`", e.id);
+            }
+        }
+
+        for (id, locs) in operators.iter() {
+            for loc in locs.iter() {
+                warning!(at: &loc.path, line: loc.line_no_or_0(), column: loc.col_no_or_0(), "`DEFINE_ENUM_FLAG_OPERATORS({})` doesn't match any known enum", id);
+            }
+        }
+    }
+
+    fn cleanup_bitfields(&mut self) {
+        let mut aggregates = [std::mem::take(&mut self.classes), std::mem::take(&mut self.structs), std::mem::take(&mut self.unions)];
+        for agg in aggregates.iter_mut().flat_map(|aggs| aggs.values_by_insert_mut()) {
+            agg.data.layout_bitfields(self);
+        }
+        let [classes, structs, unions] = aggregates;
+        self.classes = classes;
+        self.structs = structs;
+        self.unions  = unions;
+    }
+
+    fn cleanup_static_asserts(&mut self) {
+        let mut asserts = std::mem::take(&mut self.static_asserts);
+        for a in asserts.iter_mut() {
+            // conditions that don't depend on the architecture (e.g. `defined(__cplusplus)`) are assumed to hold
+            let archs = Arch::ALL.iter().copied().filter(|arch| a.condition.as_ref().and_then(|c| arch.eval_condition(c)) != Some(false));
+            a.results = archs.filter_map(|arch| Some((arch, self.eval_layout_expr(&a.expr, arch)? != 0))).collect();
+            if a.results.values().all(|r| *r) { continue }
+
+            let archs = a.results.iter().filter(|(_, r)| !**r).map(|(arch, _)| arch.to_string()).collect::<Vec<_>>().join(", ");
+            let msg = format!("`{}` does not hold for {}", a.expr, archs);
+            for ty in a.types.iter() {
+                let agg = self.structs.get_mut(ty).or_else(|| self.unions.get_mut(ty)).or_else(|| self.classes.get_mut(ty));
+                if let Some(agg) = agg { agg.issues.push(Issue::new(a.defined_at.clone(), msg.clone())); }
+            }
+        }
+        self.static_asserts = asserts;
+    }
+
+    /// The size in bytes of an integer (or `enum`) type such as `UINT`, `unsigned short`, or `D3D12_RAYTRACING_INSTANCE_FLAGS`.
+    pub(crate) fn integer_size(&self, ty: &str) -> Option<u32> {
+        let ty = ty.split_ascii_whitespace().filter(|t| !["const", "volatile", "signed", "unsigned", "enum"].contains(t)).collect::<Vec<_>>().join(" ");
+        Some(match ty.as_str() {
+            "" /* unsigned */ | "int" | "long" | "long int" | "INT" | "UINT" | "LONG" | "ULONG" | "DWORD" | "BOOL" | "INT32" | "UINT32" | "LONG32" | "ULONG32" | "DWORD32" | "__int32" => 4,
+            "char" | "CHAR" | "UCHAR" | "BYTE" | "BOOLEAN" | "INT8" | "UINT8" | "__int8" | "bool" => 1,
+            "short" | "short int" | "SHORT" | "USHORT" | "WORD" | "WCHAR" | "wchar_t" | "INT16" | "UINT16" | "__int16" => 2,
+            "long long" | "__int64" | "LONGLONG" | "ULONGLONG" | "INT64" | "UINT64" | "LONG64" | "ULONG64" | "DWORD64" => 8,
+            ty if self.enums.get(ty).is_some() => 4,
+            ty => return self.typedefs.get(ty).and_then(|t| self.integer_size(t)),
+        })
+    }
+
+    fn cleanup_errors(&mut self) {
+        let mut messages = std::mem::take(&mut self.pending_error_messages);
+        let mut errors = VecMap::default();
+        for constant in self.constants.values_by_insert() {
+            let value       = if let Some(v) = constant.value.as_deref() { v } else { continue };
+            let message     = messages.remove(&constant.id);
+            let evaluated   = if let Some(e) = self.eval(value) { e } else { continue };
+            let kind        = if let Some(k) = ErrorCode::kind_from_define(value, message.is_some(), evaluated) { k } else { continue };
+
+            let mut error = ErrorCode::new(constant.id.clone(), kind, evaluated as u32);
+            error.defined_at = constant.defined_at.clone();
+            error.message = message;
+            errors.insert(error.id.clone(), error);
+        }
+        self.errors = errors;
+    }
+
     fn cleanup_inherited_methods(&mut self) {
-        for interface in self.interfaces.values_by_key() {
-            let mut next_base = &interface.base;
-            while let Some(base) = next_base.as_ref().and_then(|base| self.interfaces.get(base)) {
-                for method in base.all_methods.keys().filter_map(|m| interface.all_methods.get(m)) {
-                    method.inherited.store(true, std::sync::atomic::Ordering::Relaxed);
+        for interface in self.interfaces.values_by_key() { self.mark_inherited_methods(interface); }
+    }
+
+    /// Compare redefinitions of interfaces once restated base methods (e.g. `QueryInterface` in a `DECLARE_INTERFACE_(ID3D10Blob, IUnknown)`) can be ignored.
+    fn cleanup_interface_redefinitions(&mut self) {
+        for (loc, interface) in std::mem::take(&mut self.pending_interface_redefinitions).into_iter() {
+            self.mark_inherited_methods(&interface);
+            let prev = if let Some(prev) = self.interfaces.get(&interface.id) { prev } else { continue };
+            let previously = prev.defined_at.iter().next().cloned().unwrap_or_default();
+            let what = format!("interface {}", prev.id);
+            let issues = prev.differences(&interface).into_iter().map(|diff| Issue::new(loc.clone(), format!("`{}` redefined differently than at `{}`: {}", what, previously, diff))).collect::<Vec<_>>();
+            if let Some(prev) = self.interfaces.get_mut(&interface.id) { prev.issues.extend(issues); }
+        }
+    }
+
+    /// Flag the methods of `interface` that restate methods of it's bases as inherited.
+    fn mark_inherited_methods(&self, interface: &Interface) {
+        let bases = interface.base.iter().flat_map(|base| std::iter::once(base).chain(self.interface_bases(base))).filter(|base| **base != interface.id);
+        for base in bases.filter_map(|base| self.interfaces.get(base)) {
+            for method in interface.all_methods.iter() {
+                // a method with the same name but a different signature is a new overload with it's own vtable slot (e.g. `ID2D1DeviceContext::CreateBitmap`)
+                let inherited = base.all_methods.iter().any(|m| m.f.id == method.f.id && m.signature() == method.signature());
+                if inherited { method.inherited.store(true, std::sync::atomic::Ordering::Relaxed); }
+            }
+        }
+    }
+
+    fn cleanup_interface_kinds(&mut self) {
+        let kinds = self.interfaces.values_by_insert().map(|interface| {
+            let bases = self.interface_bases(&interface.id);
+            for id in std::iter::once(&interface.id).chain(bases.iter().copied()) {
+                match id.as_str() {
+                    "IInspectable"  => return InterfaceKind::WinRt,
+                    "IUnknown"      => return InterfaceKind::Com,
+                    _               => {},
+                }
+            }
+            match self.interfaces.get(bases.last().copied().unwrap_or(&interface.id)).map(|i| i.base.as_ref()) {
+                Some(None)  => InterfaceKind::Vtable,
+                _           => InterfaceKind::UnknownBase, // missing or cyclic bases
+            }
+        }).collect::<Vec<_>>();
+        for (interface, kind) in self.interfaces.values_by_insert_mut().zip(kinds) { interface.kind = kind; }
+    }
+
+    fn cleanup_vtbls(&mut self) {
+        let mut issues = Vec::new();
+        for vtbl in self.vtbls.values_by_insert() {
+            let vtable  = if let Some(v) = self.vtable(&vtbl.interface) { v } else { continue }; // C only, or missing bases
+            let loc     = if let Some(loc) = vtbl.defined_at.iter().next() { loc } else { continue };
+            let c       = vtbl.slots.iter().map(|s| s.id.as_str()).collect::<Vec<_>>();
+            let cpp     = vtable.iter().map(|s| s.method.f.id.as_str()).collect::<Vec<_>>();
+
+            if c.len() != cpp.len() {
+                issues.push((vtbl.interface.clone(), Issue::new(loc.clone(), format!("C `{}` has {} slots, but C++ `{}` has {} methods", vtbl.id, c.len(), vtbl.interface, cpp.len()))));
+            }
+            if let Some(i) = (0 .. c.len().min(cpp.len())).find(|&i| c[i] != cpp[i]) {
+                let (mut c_sorted, mut cpp_sorted) = (c.clone(), cpp.clone());
+                c_sorted.sort_unstable();
+                cpp_sorted.sort_unstable();
+                let reordered = if c_sorted == cpp_sorted { " (same methods in a different order)" } else { "" };
+                issues.push((vtbl.interface.clone(), Issue::new(vtbl.slots[i].defined_at.clone(), format!("C `{}` slot {} is `{}`, but C++ `{}` has `{}`{}", vtbl.id, i, c[i], vtbl.interface, cpp[i], reordered))));
+            }
+            for (c, cpp) in vtbl.slots.iter().zip(vtable.iter()).filter(|(c, cpp)| c.id == cpp.method.f.id) {
+                if let Some(declared_by) = c.declared_by.as_ref().filter(|d| *d != cpp.declared_by) {
+                    issues.push((vtbl.interface.clone(), Issue::new(c.defined_at.clone(), format!("C `{}` slot {} `{}` is declared by `{}`, but C++ declares it in `{}`", vtbl.id, cpp.index, c.id, declared_by, cpp.declared_by))));
                 }
-                next_base = &base.base;
             }
         }
+        for (interface, issue) in issues.into_iter() {
+            if let Some(interface) = self.interfaces.get_mut(&interface) { interface.issues.push(issue); }
+        }
     }
 
     fn cleanup_macro_methods(&mut self) {
-        let macros = std::mem::replace(&mut self.macros, VecMap::default());
-        let mut macros = macros.iter_by_key().collect::<HashMap<_, _>>();
-        for interface in self.interfaces.values_by_key() {
-            let root_interface = interface;
-            let mut next_interface = Some(interface);
-            while let Some(interface) = next_interface {
-                for method in interface.methods() {
-                    for postfix in ["", "A", "W"].iter().copied() {
-                        if let Some(root_interface) = root_interface.id.strip_suffix(postfix) {
-                            let possible_macro = format!("{}_{}", root_interface, method.f.id.as_str());
-                            macros.remove(&Ident::from(possible_macro));
-                        }
+        let macros = std::mem::take(&mut self.macros);
+        let mut claimed = HashSet::new();
+        let mut results = Vec::new();
+        for interface in self.interfaces.values_by_insert() {
+            // every method callable on this interface, including inherited ones
+            let mut methods = interface.all_methods.iter().filter(|m| !m.is_inherited()).collect::<Vec<_>>();
+            for base in self.interface_bases(&interface.id).into_iter().filter_map(|base| self.interfaces.get(base)) {
+                methods.extend(base.all_methods.iter().filter(|m| !m.is_inherited()));
+            }
+
+            let mut wrappers = Vec::<MethodWrapper>::new();
+            let mut issues = Vec::new();
+            let mut prefixes = Vec::new(); // e.g. `IFoo` for `IFooW` if `IFoo_Method` wrappers were found
+            for postfix in ["", "A", "W"].iter().copied() {
+                let prefix = if let Some(prefix) = interface.id.strip_suffix(postfix) { prefix } else { continue };
+                for method in methods.iter() {
+                    let id = format!("{}_{}", prefix, method.f.id);
+                    if wrappers.iter().any(|w| w.macro_.id.as_str() == id) { continue } // overloads share a wrapper
+                    if let Some(m) = macros.get(id.as_str()) {
+                        claimed.insert(m.id.clone());
+                        wrappers.push(MethodWrapper::new(m.clone(), method.f.id.clone()));
+                        if !prefixes.contains(&prefix) { prefixes.push(prefix); }
                     }
                 }
-                next_interface = interface.base.as_ref().and_then(|base| self.interfaces.get(base));
             }
+
+            // C wrappers are generated for every method, or none at all
+            let mut missing = HashSet::new();
+            for prefix in prefixes.iter() {
+                for method in methods.iter() {
+                    let id = format!("{}_{}", prefix, method.f.id);
+                    if wrappers.iter().any(|w| w.macro_.id.as_str() == id) || !missing.insert(id.clone()) { continue }
+                    let loc = method.f.defined_at.iter().next().or_else(|| interface.defined_at.iter().next());
+                    if let Some(loc) = loc { issues.push(Issue::new(loc.clone(), format!("missing C macro wrapper `{}`", id))); }
+                }
+            }
+
+            for w in wrappers.iter() {
+                let loc = if let Some(loc) = w.macro_.defined_at.iter().next() { loc } else { continue };
+                if let Some(target) = w.target.as_ref().filter(|t| **t != w.method) {
+                    issues.push(Issue::new(loc.clone(), format!("C macro `{}` calls `{}` instead of `{}`", w.macro_.id, target, w.method)));
+                }
+                let overloads = methods.iter().filter(|m| m.f.id == w.method).collect::<Vec<_>>();
+                if !overloads.iter().any(|m| m.params.len() + 1 == w.macro_.params.len()) {
+                    let expected = overloads.iter().map(|m| (m.params.len() + 1).to_string()).collect::<Vec<_>>().join(" or ");
+                    issues.push(Issue::new(loc.clone(), format!("C macro `{}` takes {} arguments, but `{}::{}` needs {} (including `This`)", w.macro_.id, w.macro_.params.len(), interface.id, w.method, expected)));
+                }
+            }
+            results.push((wrappers, issues));
         }
-        self.macros = macros.into_iter().map(|(k, v)| (k.clone(), v.clone())).collect::<VecMap<_, _>>();
+
+        for (interface, (wrappers, issues)) in self.interfaces.values_by_insert_mut().zip(results) {
+            interface.macro_wrappers = wrappers;
+            interface.issues.extend(issues);
+        }
+        self.macros = macros.values_by_insert().filter(|m| !claimed.contains(&m.id)).map(|m| (m.id.clone(), m.clone())).collect::<VecMap<_, _>>();
     }
 
     fn impl_add_from_cpp_path(&mut self, path: &Path) -> io::Result<()> {
         let path = Arc::from(path);
         let all = std::fs::read_to_string(&path)?;
-        let mut src = SrcReader::new(path.clone(), &all);
+        self.add_from_cpp_src(path, &all)
+    }
+
+    /// Scan C++ source code as if it were the contents of `path`.
+    pub(crate) fn add_from_cpp_src(&mut self, path: Arc<Path>, all: &str) -> io::Result<()> {
+        let mut src = SrcReader::new(path.clone(), all);
 
-        let interface_by_token = false;
         let func_by_token = false;
+        let mut pack = PackStack::default();
+        let mut conditions = Conditions::default();
 
         'file1: while let Some(token) = src.next_token() {
             macro_rules! fail {
@@ -120,22 +391,97 @@ impl Root {
             }
 
             match &*token {
-                "DECLARE_INTERFACE" if interface_by_token => {
-                    let _paren      = src.next_token(); if _paren.as_deref() != Some("(") { fail!("expected `(` after `{}`, instead got {:?}", token, _paren); continue 'file1 }
-                    let _interface  = src.next_token();
-                    let _paren      = src.next_token(); if _paren.as_deref() != Some(")") { fail!("expected `)` after `{}(...`, instead got {:?}", token, _paren); continue 'file1 }
+                "#" => {
+                    // #pragma pack(push, 1)
+                    // #include <pshpack1.h>
+                    let pos = src.position();
+                    let directive = src.next_token();
+                    let result = match directive.as_deref() {
+                        Some("pragma")  => src.next_line().map_or(Ok(()), |line| pack.pragma(line.raw)),
+                        Some("include") => src.next_line().map_or(Ok(()), |line| pack.include(line.raw)),
+                        Some(keyword @ ("if" | "ifdef" | "ifndef" | "elif" | "else" | "endif")) => {
+                            // only the first branch affects packing, so e.g. `#include <pshpack1.h>` in both branches is only pushed once
+                            let mut directive = keyword.to_string();
+                            while let Some(line) = src.next_line() {
+                                directive.push(' ');
+                                directive.push_str(line.raw.trim_end_matches('\\'));
+                                if !line.raw.ends_with('\\') { break }
+                            }
+                            pack.conditional(&directive);
+                            conditions.directive(&directive).map(|_| ())
+                        },
+                        Some("define")  => {
+                            // don't mistake e.g. `#define C_ASSERT(e) typedef char __C_ASSERT__[(e)?1:-1]` for uses of `C_ASSERT` or `typedef`
+                            while let Some(line) = src.next_line() { if !line.raw.ends_with('\\') { break } }
+                            Ok(())
+                        },
+                        _               => { src.set_position(pos); Ok(()) },
+                    };
+                    if let Err(err) = result { fail!("{}", err); }
                 },
-                "DECLARE_INTERFACE_" if interface_by_token => {
-                    let _paren      = src.next_token(); if _paren.as_deref() != Some("(") { fail!("expected `(` after `{}`, instead got {:?}", token, _paren); continue 'file1 }
-                    let _interface  = src.next_token();
-                    let _comma      = src.next_token(); if _comma.as_deref() != Some(",") { fail!("expected `,` after `{}(...`, instead got {:?}", token, _comma); continue 'file1 }
-                    let _base       = src.next_token();
-                    let _paren      = src.next_token(); if _paren.as_deref() != Some(")") { fail!("expected `)` after `{}(..., ...`, instead got {:?}", token, _paren); continue 'file1 }
+                "C_ASSERT" | "static_assert" | "_STATIC_ASSERT" => {
+                    // C_ASSERT(sizeof(FOO) == 24);
+                    // static_assert(sizeof(FOO) == 24, "...");
+                    let pos = src.position();
+                    if src.next_token().as_deref() != Some("(") { src.set_position(pos); continue 'file1 }
+                    let mut args = vec![Vec::new()];
+                    let mut depth = 1;
+                    while depth > 0 {
+                        let t = expect_token!("`)` to end `{}(...`", token);
+                        match &*t {
+                            "(" | "[" | "{"     => depth += 1,
+                            ")" | "]" | "}"     => { depth -= 1; if depth == 0 { break } },
+                            "," if depth == 1   => { args.push(Vec::new()); continue },
+                            _                   => {},
+                        }
+                        args.last_mut().unwrap().push(t);
+                    }
+
+                    let expr = args[0].iter().map(|t| &**t).collect::<Vec<_>>().join(" ");
+                    let mut a = StaticAssert::new(src.token_to_location(token), expr);
+                    a.condition = conditions.current();
+                    a.message = args.get(1).map(|m| m.iter().map(|t| t.trim_matches('"')).collect::<Vec<_>>().join(""));
+                    for (i, t) in args[0].iter().enumerate() {
+                        if !StaticAssert::TYPE_OPERATORS.contains(&&**t) { continue }
+                        let ty = args[0][i+1..].iter().map(|t| &**t).find(|t| !["(", "struct", "union", "const"].contains(t));
+                        if let Some(ty) = ty.filter(|ty| valid_name(ty)) { a.types.insert(Ident::own(ty)); }
+                    }
+                    self.static_asserts.push(a);
                 },
-                "MIDL_INTERFACE" if interface_by_token => {
-                    let _paren      = src.next_token(); if _paren.as_deref() != Some("(") { fail!("expected `(` after `{}`, instead got {:?}", token, _paren); continue 'file1 }
-                    let _interface  = src.next_token();
-                    let _paren      = src.next_token(); if _paren.as_deref() != Some(")") { fail!("expected `)` after `{}(...`, instead got {:?}", token, _paren); continue 'file1 }
+                "DECLARE_INTERFACE" | "DECLARE_INTERFACE_" | "DECLARE_INTERFACE_IID" | "DECLARE_INTERFACE_IID_" => {
+                    // DECLARE_INTERFACE(IFoo) {
+                    // DECLARE_INTERFACE_(IFoo, IBase) {
+                    // DECLARE_INTERFACE_IID_(IFoo, IBase, "00000000-0000-0000-C000-000000000046") {
+                    let pos = src.position();
+                    let mut args = Vec::new();
+                    if src.next_token().as_deref() == Some("(") {
+                        while let Some(arg) = src.next_token() {
+                            args.push(arg);
+                            match src.next_token().as_deref() {
+                                Some(",")   => continue,
+                                Some(")")   => break,
+                                _           => { args.clear(); break },
+                            }
+                        }
+                    }
+                    let has_iid     = token.starts_with("DECLARE_INTERFACE_IID");
+                    let has_base    = token.ends_with('_');
+                    let open        = src.next_token();
+                    if args.len() != 1 + has_base as usize + has_iid as usize || open.as_deref() != Some("{") {
+                        fail!("expected `{}(...) {{`", token);
+                        src.set_position(pos);
+                        continue 'file1
+                    }
+
+                    let iid = if has_iid {
+                        let iid = Guid::parse(args.last().unwrap());
+                        if iid.is_none() { fail!("expected a valid GUID in `{}(..., \"...\")`", token); }
+                        iid
+                    } else {
+                        None
+                    };
+                    let base = if has_base { Some(&*args[1]) } else { None };
+                    self.add_interface_from_cpp(&mut src, args[0], base, iid)?;
                 },
                 "WINAPI" if func_by_token => {
                     let abi = token;
@@ -151,6 +497,130 @@ impl Root {
                         }
                     }
                 },
+                "DECLSPEC_UUID" | "__declspec" => {
+                    // interface DECLSPEC_UUID("...") DECLSPEC_NOVTABLE IFoo ...
+                    // struct __declspec(uuid("...")) __declspec(novtable) IFoo ...
+                    let pos = src.position();
+                    let iid = if let Some(iid) = parse_uuid_declspec(&mut src, &token) { iid } else { continue 'file1 }; // e.g. `__declspec(dllimport)`
+                    let name = if let Some(name) = skip_declspecs(&mut src, &mut Declspecs::default()) { name } else { src.set_position(pos); continue 'file1 };
+                    if !Interface::valid_name(&name) { src.set_position(pos); continue 'file1 }
+                    self.pending_iids.push(PendingIid { location: src.token_to_location(name), interface: Ident::own(&name), iid: Some(iid), constant: None });
+                },
+                "IID" => {
+                    // EXTERN_C const IID IID_IFoo;
+                    let pos = src.position();
+                    let name = src.next_token();
+                    let semi = src.next_token();
+                    match (name, semi) {
+                        (Some(name), Some(semi)) if semi == ";" && name.starts_with("IID_") => {
+                            let interface = Ident::own(&name["IID_".len()..]);
+                            self.pending_iids.push(PendingIid { location: src.token_to_location(name), interface, iid: None, constant: Some(Ident::own(&name)) });
+                        },
+                        _ => src.set_position(pos),
+                    }
+                },
+                "DEFINE_GUID" => {
+                    // DEFINE_GUID(IID_IFoo, 0x81bdcbca, 0x64d4, 0x426d, 0xae, 0x8d, 0xad, 0x1, 0x47, 0xf4, 0x27, 0x5c);
+                    let pos = src.position();
+                    let _paren  = src.next_token(); if _paren.as_deref() != Some("(") { src.set_position(pos); continue 'file1 }
+                    let name    = if let Some(name) = src.next_token() { name } else { continue 'file1 };
+                    let mut args = Vec::new();
+                    while let Some(comma_or_paren) = src.next_token() {
+                        if comma_or_paren != "," { break }
+                        if let Some(arg) = src.next_token() { args.push(arg) }
+                    }
+                    let interface = if let Some(interface) = name.strip_prefix("IID_") { Ident::own(interface) } else { continue 'file1 }; // e.g. CLSID_*
+                    let args = args.iter().map(|a| &**a).collect::<Vec<_>>();
+                    if let Some(iid) = Guid::from_define_guid_args(&args) {
+                        self.pending_iids.push(PendingIid { location: src.token_to_location(name), interface, iid: Some(iid), constant: Some(Ident::own(&name)) });
+                    } else {
+                        fail!("expected 11 integer GUID components after `DEFINE_GUID({},`", name);
+                    }
+                },
+                "DEFINE_ENUM_FLAG_OPERATORS" => {
+                    // DEFINE_ENUM_FLAG_OPERATORS(D3D12_RESOURCE_FLAGS);
+                    let pos = src.position();
+                    let paren_open  = src.next_token();
+                    let name        = src.next_token();
+                    let paren_close = src.next_token();
+                    match (paren_open, name, paren_close) {
+                        (Some(o), Some(name), Some(c)) if o == "(" && c == ")" && Enum::valid_name(&name) => {
+                            self.pending_flag_operators.push((src.token_to_location(name), Ident::own(&name)));
+                        },
+                        _ => src.set_position(pos), // e.g. `#define DEFINE_ENUM_FLAG_OPERATORS(ENUMTYPE) ...`
+                    }
+                },
+                "DECLARE_HANDLE" => {
+                    // DECLARE_HANDLE(HWND); -> struct HWND__{int unused;}; typedef struct HWND__ *HWND
+                    let pos = src.position();
+                    let paren_open  = src.next_token();
+                    let name        = src.next_token();
+                    let paren_close = src.next_token();
+                    match (paren_open, name, paren_close) {
+                        (Some(o), Some(name), Some(c)) if o == "(" && c == ")" && valid_name(&name) => {
+                            self.add_typedef(&name, &format!("struct {}__", name), 1);
+                        },
+                        _ => src.set_position(pos), // e.g. `#define DECLARE_HANDLE(name) ...`
+                    }
+                },
+                "struct" | "class" | "union" | "interface" | "MIDL_INTERFACE" => {
+                    // struct CD3DX12_RECT : public D3D12_RECT { ... };
+                    // struct __declspec(uuid("...")) __declspec(novtable) IFoo : public IUnknown { ... };
+                    // interface DECLSPEC_UUID("...") DECLSPEC_NOVTABLE IFoo : public IUnknown { ... };
+                    // interface DECLSPEC_UUID("...") DECLSPEC_NOVTABLE IFoo;
+                    // MIDL_INTERFACE("...") IFoo : public IUnknown { ... };
+                    let pos = src.position();
+                    let mut specs = Declspecs::default();
+                    if token == "MIDL_INTERFACE" {
+                        specs.uuid = parse_uuid_declspec(&mut src, "DECLSPEC_UUID");
+                        specs.novtable = true;
+                        if specs.uuid.is_none() { fail!("expected a valid GUID in `MIDL_INTERFACE(\"...\")`"); src.set_position(pos); continue 'file1 }
+                    }
+                    let name = if let Some(name) = skip_declspecs(&mut src, &mut specs) { name } else { continue 'file1 };
+                    if !valid_name(&name) { src.set_position(pos); continue 'file1 }
+
+                    let mut bases = Vec::new();
+                    let mut brace = expect_token!("`{{`, `:`, or `;` after `{} {}`", token, name);
+                    if brace == ":" {
+                        let mut base = Vec::new();
+                        loop {
+                            brace = expect_token!("`{{` after `{} {} : ...`", token, name);
+                            match &*brace {
+                                "{" | ";" | "(" | ")" => break,
+                                ","                 => bases.push(std::mem::take(&mut base).join(" ")),
+                                "public" | "protected" | "private" | "virtual" => {},
+                                t                   => base.push(t.to_string()),
+                            }
+                        }
+                        if !base.is_empty() { bases.push(base.join(" ")) }
+                    }
+                    if brace == ";" {
+                        // forward declaration
+                        if let Some(iid) = specs.uuid.filter(|_| Interface::valid_name(&name)) {
+                            self.pending_iids.push(PendingIid { location: src.token_to_location(name), interface: Ident::own(&name), iid: Some(iid), constant: None });
+                        }
+                        continue 'file1
+                    }
+                    if brace != "{" { src.set_position(pos); continue 'file1 } // `struct Foo *` parameter, etc.
+
+                    let interface = specs.uuid.is_some() || specs.novtable || (token == "interface" && !bases.is_empty()); // `interface IFoo { CONST_VTBL struct IFooVtbl *lpVtbl; };` is C
+                    if interface {
+                        if !Interface::valid_name(&name) { continue 'file1 }
+                        if bases.len() > 1 { fail!("multiple inheritance not supported: `{} {} : {}`", token, name, bases.join(", ")); }
+                        self.add_interface_from_cpp(&mut src, name, bases.first().map(|b| b.as_str()), specs.uuid)?;
+                        continue 'file1
+                    }
+                    if token == "interface" || !Aggregate::valid_name(&name) { src.set_position(pos); continue 'file1 }
+
+                    let loc = src.token_to_location(name);
+                    let mut s = Aggregate::new(AggregateCategory::from_str(&token).unwrap(), Ident::own(&name));
+                    s.pack = pack.current;
+                    s.align = specs.align;
+                    s.base = bases.first().map(|b| Ident::own(b));
+                    if bases.len() > 1 { s.issues.push(Issue::new(loc.clone(), format!("multiple inheritance not supported: `{} {} : {}`", token, name, bases.join(", ")))); }
+                    let _ = s.add_from_cpp(name, &mut src, false);
+                    self.add_aggregate(&loc, s);
+                },
                 "typedef" => {
                     let category = expect_token!("`enum`, `struct`, `interface`, or `union` after `typedef`");
                     match &*category {
@@ -159,16 +629,85 @@ impl Root {
                         "interface" => {},
                         "struct"    => {},
                         "union"     => {},
-                        _other      => continue 'file1, // `typedef Foo Bar;` or similar
+                        _other      => {
+                            // typedef Foo Bar;
+                            // typedef Foo Bar, *PBar;
+                            let mut declarators = vec![vec![category]];
+                            loop {
+                                let token = expect_token!("`;` after `typedef {}`", category);
+                                match &*token {
+                                    ";" => break,
+                                    "," => declarators.push(Vec::new()),
+                                    _   => declarators.last_mut().unwrap().push(token),
+                                }
+                            }
+                            if declarators.iter().flatten().any(|t| ["(", "[", "{"].contains(&&**t)) { continue 'file1 } // function pointers, arrays, etc.
+
+                            let mut first = declarators[0].iter().map(|t| &**t).collect::<Vec<_>>();
+                            let name = if let Some(name) = first.pop() { name } else { continue 'file1 };
+                            let base = first.iter().copied().take_while(|t| *t != "*").collect::<Vec<_>>().join(" ");
+                            let pointers = first.iter().filter(|t| **t == "*").count();
+                            self.add_typedef(name, &base, pointers);
+                            for declarator in declarators.iter().skip(1) {
+                                if let Some((name, pointers)) = declarator.split_last() {
+                                    self.add_typedef(name, &base, pointers.iter().filter(|t| **t == "*").count());
+                                }
+                            }
+                            continue 'file1
+                        },
                     }
 
                     let mut enum_class   = false;
+                    let mut specs   = Declspecs::default();
+                    let name_pos    = src.position();
                     let mut name    = expect_token!("name after `typedef {}`", category);
+                    if ["DECLSPEC_ALIGN", "DECLSPEC_UUID", "DECLSPEC_NOVTABLE", "__declspec"].contains(&&*name) {
+                        // typedef struct DECLSPEC_ALIGN(16) _M128A { ... } M128A;
+                        // typedef interface DECLSPEC_UUID("...") IFoo { ... } IFoo;
+                        src.set_position(name_pos);
+                        name = if let Some(name) = skip_declspecs(&mut src, &mut specs) { name } else { continue 'file1 };
+                    }
                     if category == "enum" && name == "class" {
                         enum_class = true;
                         name = expect_token!("name after `typedef enum class`");
                     }
 
+                    if category == "interface" {
+                        // typedef interface IFoo IFoo;
+                        // typedef interface IFoo : public IBase { ... } IFoo;
+                        // typedef interface IFoo { CONST_VTBL struct IFooVtbl *lpVtbl; } IFoo;
+                        let mut base = None;
+                        let mut open_brace = expect_token!("`{{`, `:`, or name after `typedef interface {}`", name);
+                        if open_brace == ":" {
+                            loop {
+                                open_brace = expect_token!("`{{` after `typedef interface {} : ...`", name);
+                                match &*open_brace {
+                                    "{" | ";"                                       => break,
+                                    "public" | "protected" | "private" | "virtual"  => {},
+                                    _ if base.is_none()                             => base = Some(open_brace),
+                                    _                                               => {},
+                                }
+                            }
+                        }
+                        if open_brace != "{" || !Interface::valid_name(&name) { continue 'file1 }
+
+                        let body = src.position();
+                        let mut depth = 1;
+                        let mut c_vtbl = false;
+                        while depth > 0 {
+                            match &*expect_token!("`}}` to end `typedef interface {}`", name) {
+                                "{"         => depth += 1,
+                                "}"         => depth -= 1,
+                                "lpVtbl"    => c_vtbl = true,
+                                _           => {},
+                            }
+                        }
+                        if c_vtbl { continue 'file1 } // C view of an interface - see `IFooVtbl`
+                        src.set_position(body);
+                        self.add_interface_from_cpp(&mut src, name, base.as_deref(), specs.uuid)?;
+                        continue 'file1
+                    }
+
                     let open_brace  = expect_token!("`{{` or `;` after `typedef {} {}`", category, name);
                     match &*open_brace {
                         ";" => continue 'file1,
@@ -188,15 +727,19 @@ impl Root {
                             self.add_enum(&loc, e);
                         },
                         "class" | "interface" | "struct" | "union" => {
-                            if category == "struct" && name.ends_with("Vtbl") {
-                                // ignore: typedef struct IUnknownVtbl { BEGIN_INTERFACE ... END_INTERFACE } IUnknownVtbl;
-                                while let Some(t) = src.next_token() {
-                                    if t == "END_INTERFACE" || t == "}" { break }
+                            if category == "struct" && Vtbl::valid_name(&name) {
+                                // typedef struct IUnknownVtbl { BEGIN_INTERFACE ... END_INTERFACE } IUnknownVtbl;
+                                let mut vtbl = Vtbl::new(Ident::own(&name));
+                                match vtbl.add_from_cpp(&mut src) {
+                                    Ok(())  => self.add_vtbl(&loc, vtbl),
+                                    Err(()) => fail!("expected `( STDMETHODCALLTYPE *Method )( ... );` slots in `{}`", name),
                                 }
                                 continue 'file1;
                             }
                             let cat = AggregateCategory::from_str(&*category).unwrap();
                             let mut s = Aggregate::new(cat, Ident::own(&*name));
+                            s.pack  = pack.current;
+                            s.align = specs.align;
                             let _ = s.add_from_cpp(name, &mut src, true);
                             self.add_aggregate(&src.token_to_location(name), s);
                         },
@@ -211,68 +754,75 @@ impl Root {
 
         src.reset();
 
+        let mut message_id      : Option<&str>      = None;
+        let mut message_text    : Option<String>    = None;
         'file2: while let Some(line) = src.next_line() {
-            let unexpected_eof  = |e| unexpected_eof(&line.location, e);
-            let warn_expected   = |e| warn_expected(&line.location, e);
+            if !(line.trimmed.is_empty() || line.trimmed.starts_with("//") || line.trimmed.starts_with('#')) {
+                message_id = None;
+                message_text = None;
+            }
 
             if let Some(pp) = line.trimmed.strip_prefix("#") {
                 // Preprocessor command (#ifdef, #if, #else, #endif, #define, #include, etc.)
                 let pp = pp.trim_start();
                 if let Some(define) = pp.strip_prefix("define ") {
-                    let define = define.trim_start();
-                    if let Some(end_of_ident) = define.find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_')) {
-                        let (ident, rest) = define.split_at(end_of_ident);
-                        if rest.starts_with('(') { // macro ala #define FOO(...
-                            self.add_macro(&line.location, Macro::new(Ident::own(ident)));
-                        } else if rest.trim().is_empty() { // empty
-                            // ...
-                        } else if rest.chars().next().unwrap_or('\0').is_ascii_whitespace() { // constant? ala #define FOO ...
-                            // TODO: separate constant #define s from type aliases? Or is that too complicated? Rename?
-                            self.add_constant(&line.location, Constant::new(Ident::own(ident)));
-                        } else { // syntax error?
-                            // ...
+                    let mut define = define.without_cpp_comments().trim().to_string();
+                    while define.ends_with('\\') { // line continuation
+                        define.pop();
+                        let next = if let Some(next) = src.next_line() { next } else { break };
+                        define.push(' ');
+                        define.push_str(next.trimmed.without_cpp_comments().trim());
+                    }
+                    let define = define.trim();
+
+                    let end_of_ident = define.find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_')).unwrap_or(define.len());
+                    let (ident, rest) = define.split_at(end_of_ident);
+                    if let Some(params_body) = rest.strip_prefix('(') { // macro ala #define FOO(...
+                        let mut m = Macro::new(Ident::own(ident));
+                        if let Some((params, body)) = params_body.split_once(')') {
+                            m.params = params.split(',').map(str::trim).filter(|p| !p.is_empty()).map(Ident::own).collect();
+                            m.body = body.trim().into();
                         }
+                        self.add_macro(&line.location, m);
+                    } else if rest.trim().is_empty() { // empty
+                        // ...
+                    } else if rest.chars().next().unwrap_or('\0').is_ascii_whitespace() { // constant? ala #define FOO ...
+                        // TODO: separate constant #define s from type aliases? Or is that too complicated? Rename?
+                        let mut c = Constant::new(Ident::own(ident));
+                        c.value = Some(rest.trim().into());
+                        if let Some(message) = message_text.take().filter(|_| message_id == Some(ident)) {
+                            self.pending_error_messages.insert(c.id.clone(), message.trim().into());
+                        }
+                        self.add_constant(&line.location, c);
+                    } else { // syntax error?
+                        // ...
                     }
                 }
                 // else #ifdef, #if, #else, #endif, #include, ...
-            } else if let Some(_cpp_comment) = line.trimmed.strip_prefix("//") {
+                message_id = None;
+                message_text = None;
+            } else if let Some(cpp_comment) = line.trimmed.strip_prefix("//") {
                 // C++ style single line comment
-            } else if let Some(interface) = line.trimmed.strip_prefix_suffix("DECLARE_INTERFACE(", ")") {
-                let mut interface = Interface::new(Ident::own(interface), None);
-                let err = interface.add_from_cpp(&line.location, &mut src);
-                self.add_interface(&line.location, interface);
-                err?;
-            } else if let Some(interface_base) = line.trimmed.strip_prefix_suffix("DECLARE_INTERFACE_(", ")") {
-                let (interface, base) = interface_base.split_once_trim(",").ok_or_else(|| warn_expected("comma in `DECLARE_INTERFACE_(Interface, Base)`"))?;
-                let mut interface = Interface::new(Ident::own(interface), Some(Ident::own(base)));
-                let err = interface.add_from_cpp(&line.location, &mut src);
-                self.add_interface(&line.location, interface);
-                err?;
-            } else if let Some(_midl) = line.trimmed.strip_prefix_suffix("MIDL_INTERFACE(\"", "\")") {
-                let line = src.next_line().ok_or_else(|| unexpected_eof("interface line following `MIDL_INTERFACE(\"...\")`"))?;
-                if let Some((interface, vis_base)) = line.trimmed.split_once_trim(":") {
-                    let mut interface = Interface::new(Ident::own(interface), None);
-                    if let Some((vis, base)) = vis_base.split_once_trim(" ") {
-                        match vis {
-                            "public"    => {},
-                            "protected" => drop(warn_expected("public, not protected")),
-                            "private"   => drop(warn_expected("public, not private")),
-                            _other      => drop(warn_expected("public, protected, or private inheritance")),
-                        }
-                        interface.base = Some(Ident::own(base));
-                    } else {
-                        interface.base = Some(Ident::own(vis_base));
+                //
+                // winerror.h and ntstatus.h document their error codes like so:
+                //
+                // MessageId: ERROR_FILE_NOT_FOUND
+                //
+                // MessageText:
+                //
+                // The system cannot find the file specified.
+                //
+                let comment = cpp_comment.trim();
+                if let Some(id) = comment.strip_prefix("MessageId:") {
+                    message_id = Some(id.trim());
+                    message_text = None;
+                } else if comment == "MessageText:" && message_id.is_some() {
+                    message_text = Some(String::new());
+                } else if let Some(text) = message_text.as_mut() {
+                    if !text.is_empty() || !comment.is_empty() {
+                        text.push_str(comment);
+                        text.push('\n');
                     }
-                    let err = interface.add_from_cpp(&line.location, &mut src);
-                    self.add_interface(&line.location, interface);
-                    err?;
-                } else if line.trimmed == "IUnknown" {
-                    let mut interface = Interface::new(Ident::own(line.trimmed), None);
-                    let err = interface.add_from_cpp(&line.location, &mut src);
-                    self.add_interface(&line.location, interface);
-                    err?;
-                } else {
-                    warn_expected("`Interface : Base` line following `MIDL_INTERFACE(\"...\")`");
                 }
             } else if let Some(winapi) = line.trimmed.find_token("WINAPI") {
                 //let _ret = line.trimmed[..winapi].trim_end();
@@ -291,11 +841,17 @@ impl Root {
         Ok(())
     }
 
-    fn add_interface(&mut self, loc: &Location, mut interface: Interface) {
-        let path = &*loc.path;
-        let line_no = loc.line_no_or_0();
-        let col_no  = loc.col_no_or_0();
+    /// Parse the body of an interface, given everything up to and including the opening `{` has already been parsed.
+    fn add_interface_from_cpp(&mut self, src: &mut SrcReader, name: SrcToken, base: Option<&str>, iid: Option<Guid>) -> io::Result<()> {
+        let loc = src.token_to_location(name);
+        let mut interface = Interface::new(Ident::own(&name), base.map(Ident::own));
+        interface.iid = iid;
+        let err = interface.add_from_cpp(&loc, src);
+        self.add_interface(&loc, interface);
+        err
+    }
 
+    fn add_interface(&mut self, loc: &Location, mut interface: Interface) {
         match self.interfaces.entry(interface.id.clone()) {
             vec_map::Entry::Vacant(entry) => {
                 interface.defined_at.insert(loc.clone());
@@ -303,22 +859,21 @@ impl Root {
             },
             vec_map::Entry::Occupied(mut entry) => {
                 let prev = entry.get_mut();
+                if prev.iid.is_none() { prev.iid = interface.iid; }
                 prev.defined_at.insert(loc.clone());
-                let mut new_methods  = interface.methods().map(|m| m.f.id.as_str());
-                let mut prev_methods = prev     .methods().map(|m| m.f.id.as_str());
-                let interface = &interface.id;
-                loop {
-                    match (prev_methods.next(), new_methods.next()) {
-                        (Some(prev), Some(new)) if prev < new => warning!(at: path, line: line_no, column: col_no, "duplicate interface `{}` missing previous method `{}`", interface, prev),
-                        (Some(prev), Some(new)) if prev > new => warning!(at: path, line: line_no, column: col_no, "duplicate interface `{}` adds new method `{}`", interface, new),
-                        (Some(_prev), Some(_new)) => continue,
-
-                        (Some(prev), None)  => warning!(at: path, line: line_no, column: col_no, "duplicate interface `{}` missing previous method `{}`", interface, prev),
-                        (None, Some(new))   => warning!(at: path, line: line_no, column: col_no, "duplicate interface `{}` adds new method `{}`", interface, new),
-                        (None, None)        => {},
-                    }
-                    break;
-                }
+                self.pending_interface_redefinitions.push((loc.clone(), interface)); // compared by `cleanup_interface_redefinitions`
+            },
+        }
+    }
+
+    fn add_vtbl(&mut self, loc: &Location, mut vtbl: Vtbl) {
+        match self.vtbls.entry(vtbl.id.clone()) {
+            vec_map::Entry::Vacant(entry) => {
+                vtbl.defined_at.insert(loc.clone());
+                entry.insert(vtbl);
+            },
+            vec_map::Entry::Occupied(mut entry) => {
+                entry.get_mut().defined_at.insert(loc.clone());
             },
         }
     }
@@ -338,10 +893,16 @@ impl Root {
         }
     }
 
-    fn add_aggregate(&mut self, _loc: &Location, a: Aggregate) {
+    fn add_typedef(&mut self, name: &str, base: &str, pointers: usize) {
+        if base.is_empty() || !valid_name(name) { return }
+        let ty = format!("{}{}", base, " *".repeat(pointers));
+        if ty != name { self.typedefs.entry(Ident::own(name)).or_insert(ty); }
+    }
+
+    fn add_aggregate(&mut self, loc: &Location, a: Aggregate) {
         let agg = match a.category {
             AggregateCategory::Class        => &mut self.classes,
-            AggregateCategory::Interface    => return, // TODO: implement
+            AggregateCategory::Interface    => return, // parsed as an `Interface` by `add_interface_from_cpp` instead
             AggregateCategory::Struct       => &mut self.structs,
             AggregateCategory::Union        => &mut self.unions,
         };
@@ -349,21 +910,33 @@ impl Root {
             vec_map::Entry::Vacant(entry) => {
                 entry.insert(a);
             },
-            vec_map::Entry::Occupied(entry) => {
-                let _prev = entry.get();
-                // TODO: fields, layout?
+            vec_map::Entry::Occupied(mut entry) => {
+                let prev = entry.get_mut();
+                let previously = prev.defined_at.iter().next().cloned().unwrap_or_default();
+                let what = format!("{} {}", prev.category, prev.id);
+                for diff in prev.data.differences(&a.data) {
+                    prev.issues.push(Issue::new(loc.clone(), format!("`{}` redefined differently than at `{}`: {}", what, previously, diff)));
+                }
+                prev.issues.extend(a.data.issues);
+                prev.defined_at.extend(a.defined_at);
             },
         }
     }
 
-    fn add_enum(&mut self, _loc: &Location, e: Enum) {
+    fn add_enum(&mut self, loc: &Location, e: Enum) {
         match self.enums.entry(e.id.clone()) {
             vec_map::Entry::Vacant(entry) => {
                 entry.insert(e);
             },
-            vec_map::Entry::Occupied(entry) => {
-                let _prev = entry.get();
-                // TODO: values, abi?
+            vec_map::Entry::Occupied(mut entry) => {
+                let prev = entry.get_mut();
+                let previously = prev.defined_at.iter().next().cloned().unwrap_or_default();
+                let what = format!("enum {}", prev.id);
+                for diff in prev.data.differences(&e.data) {
+                    prev.issues.push(Issue::new(loc.clone(), format!("`{}` redefined differently than at `{}`: {}", what, previously, diff)));
+                }
+                prev.issues.extend(e.data.issues);
+                prev.defined_at.extend(e.defined_at);
             },
         }
     }
@@ -396,3 +969,510 @@ impl Root {
         }
     }
 }
+
+
+
+impl ExprContext for Root {
+    fn constant(&self, id: &str) -> Option<&str> { self.constants.get(id)?.value.as_deref() }
+    fn macro_(&self, id: &str) -> Option<(&[Ident], &str)> { self.macros.get(id).map(|m| (&m.params[..], m.body.as_str())) }
+    fn value(&self, id: &str) -> Option<i64> { self.enumerators.get(id).copied() }
+}
+
+
+
+/// Parse the remainder of `DECLSPEC_UUID("...")` or `__declspec(uuid("..."))`, given `keyword` has already been parsed.
+///
+/// On failure (e.g. `__declspec(dllimport)`), nothing is consumed.
+fn parse_uuid_declspec(src: &mut SrcReader, keyword: &str) -> Option<Guid> {
+    fn expect(src: &mut SrcReader, expected: &str) -> Option<()> { src.next_token().filter(|t| *t == expected).map(|_| ()) }
+    let pos = src.position();
+    let iid = (|| {
+        let declspec = keyword == "__declspec";
+        if declspec { expect(src, "(")?; expect(src, "uuid")?; }
+        expect(src, "(")?;
+        let iid = Guid::parse(&src.next_token()?)?;
+        expect(src, ")")?;
+        if declspec { expect(src, ")")?; }
+        Some(iid)
+    })();
+    if iid.is_none() { src.set_position(pos); }
+    iid
+}
+
+/// The interesting parts of `DECLSPEC_*` / `__declspec(...)` groups preceding a type name.
+#[derive(Default)]
+struct Declspecs {
+    /// `DECLSPEC_ALIGN(n)` or `__declspec(align(n))`
+    align:      Option<u32>,
+
+    /// `DECLSPEC_UUID("...")` or `__declspec(uuid("..."))`
+    uuid:       Option<Guid>,
+
+    /// `DECLSPEC_NOVTABLE` or `__declspec(novtable)`
+    novtable:   bool,
+}
+
+/// Skip `DECLSPEC_NOVTABLE`, `__declspec(...)`, etc. and return the following token (typically a type name.)
+/// Any alignment, uuid, or novtable specifiers are written to `specs`.
+///
+/// On failure (e.g. `DECLSPEC_ALIGN` without `(...)`, or unbalanced parens), nothing is consumed.
+fn skip_declspecs<'t>(src: &mut SrcReader<'t>, specs: &mut Declspecs) -> Option<SrcToken<'t>> {
+    let pos = src.position();
+    let name = (|| loop {
+        let token = src.next_token()?;
+        match &*token {
+            "DECLSPEC_NOVTABLE" => specs.novtable = true,
+            "DECLSPEC_NOTHROW" => continue,
+            "DECLSPEC_UUID" | "DECLSPEC_ALIGN" | "__declspec" | "DX_DECLARE_INTERFACE" | "DWRITE_DECLARE_INTERFACE" => {
+                let mut group = vec![token, src.next_token().filter(|t| *t == "(")?];
+                let mut depth = 1;
+                while depth > 0 {
+                    let token = src.next_token()?;
+                    group.push(token);
+                    match &*token {
+                        "(" => depth += 1,
+                        ")" => depth -= 1,
+                        _ => {},
+                    }
+                }
+                if let Some(a) = declspec_align(group.iter().map(|t| &**t)) { specs.align = Some(a); }
+                // `#define DX_DECLARE_INTERFACE(x) DECLSPEC_UUID(x) DECLSPEC_NOVTABLE` (d2d1.h, dcommon.h), likewise `DWRITE_DECLARE_INTERFACE` (dwrite.h)
+                let uuid_novtable = group[0] == "DX_DECLARE_INTERFACE" || group[0] == "DWRITE_DECLARE_INTERFACE";
+                if group[0] == "DECLSPEC_UUID" || uuid_novtable || group.iter().any(|t| *t == "uuid") { specs.uuid = group.iter().find_map(|t| Guid::parse(t)); }
+                if uuid_novtable || group.iter().any(|t| *t == "novtable") { specs.novtable = true; }
+            },
+            _ => return Some(token),
+        }
+    })();
+    if name.is_none() { src.set_position(pos); }
+    name
+}
+
+
+
+#[cfg(test)] impl Root {
+    pub(crate) fn from_cpp_src(src: &str) -> Self {
+        let mut root = Root::new();
+        root.add_from_cpp_src(Path::new("test.h").into(), src).unwrap();
+        root.cleanup();
+        root
+    }
+}
+
+#[test] fn test_bitfields() {
+    let root = Root::from_cpp_src(r#"
+        #define D3D12_SHADER_COMPONENT_MAPPING_SHIFT 3
+        typedef enum D3D12_RAYTRACING_INSTANCE_FLAGS { D3D12_RAYTRACING_INSTANCE_FLAG_NONE = 0 } D3D12_RAYTRACING_INSTANCE_FLAGS;
+
+        typedef struct D3D12_RAYTRACING_INSTANCE_DESC {
+            UINT InstanceID : 24;
+            UINT InstanceMask : 8;
+            UINT InstanceContributionToHitGroupIndex : 24;
+            D3D12_RAYTRACING_INSTANCE_FLAGS Flags : 8;
+            D3D12_GPU_VIRTUAL_ADDRESS AccelerationStructure;
+        } D3D12_RAYTRACING_INSTANCE_DESC;
+
+        typedef struct Mixed {
+            UINT a : D3D12_SHADER_COMPONENT_MAPPING_SHIFT;
+            UINT b : (1 << 2);
+            UINT64 c : 2;
+            UINT64 : 0;
+            UINT64 d : 62;
+            BYTE e : 9;
+        } Mixed;
+
+        typedef struct Padded {
+            unsigned int a : 4;
+            unsigned int : 4;
+            unsigned int b : 8;
+            long long : 0;
+            unsigned char c : 2;
+        } Padded;
+    "#);
+
+    let layout = |s: &str, f: &str| {
+        let f = root.structs.get(s).unwrap().fields.get(f).unwrap();
+        f.bit_layout.map(|l| (f.bits.map_or(0, |b| b.get()), l.storage_unit, l.storage_size, l.bit_offset))
+    };
+
+    assert_eq!(layout("D3D12_RAYTRACING_INSTANCE_DESC", "InstanceID"),                              Some((24, 0, 4,  0)));
+    assert_eq!(layout("D3D12_RAYTRACING_INSTANCE_DESC", "InstanceMask"),                            Some(( 8, 0, 4, 24)));
+    assert_eq!(layout("D3D12_RAYTRACING_INSTANCE_DESC", "InstanceContributionToHitGroupIndex"),     Some((24, 1, 4,  0)));
+    assert_eq!(layout("D3D12_RAYTRACING_INSTANCE_DESC", "Flags"),                                   Some(( 8, 1, 4, 24)));
+    assert_eq!(layout("D3D12_RAYTRACING_INSTANCE_DESC", "AccelerationStructure"),                   None);
+    assert!(root.structs.get("D3D12_RAYTRACING_INSTANCE_DESC").unwrap().issues.is_empty());
+
+    assert_eq!(layout("Mixed", "a"), Some(( 3, 0, 4, 0)));
+    assert_eq!(layout("Mixed", "b"), Some(( 4, 0, 4, 3)));
+    assert_eq!(layout("Mixed", "c"), Some(( 2, 1, 8, 0)));
+    assert_eq!(layout("Mixed", "d"), Some((62, 2, 8, 0)));
+    assert_eq!(layout("Mixed", "e"), None);
+    assert_eq!(root.structs.get("Mixed").unwrap().issues.len(), 1);
+
+    let padded = root.structs.get("Padded").unwrap();
+    assert!(padded.issues.is_empty(), "{:?}", padded.issues);
+    let fields = padded.fields.values_by_insert().map(|f| f.id.as_str()).collect::<Vec<_>>();
+    assert_eq!(fields, ["a", "_anonymous_bitfield_0", "b", "_anonymous_bitfield_1", "c"]);
+    assert!(matches!(&padded.fields.get("_anonymous_bitfield_0").unwrap().ty, Type::Basic(ty) if ty.as_str() == "unsigned int"));
+    assert!(matches!(&padded.fields.get("_anonymous_bitfield_1").unwrap().ty, Type::Basic(ty) if ty.as_str() == "long long"));
+    assert_eq!(layout("Padded", "a"), Some((4, 0, 4, 0)));
+    assert_eq!(layout("Padded", "b"), Some((8, 0, 4, 8)));
+    assert_eq!(layout("Padded", "c"), Some((2, 1, 1, 0)));
+}
+
+#[test] fn test_static_asserts() {
+    let root = Root::from_cpp_src(r#"
+        #define C_ASSERT(e) typedef char __C_ASSERT__[(e)?1:-1]
+        typedef struct _LIST_ENTRY {
+            struct _LIST_ENTRY *Flink;
+            struct _LIST_ENTRY *Blink;
+        } LIST_ENTRY;
+        C_ASSERT(sizeof(LIST_ENTRY) == 16);
+        C_ASSERT(FIELD_OFFSET(LIST_ENTRY, Blink) == 4);
+        static_assert(TYPE_ALIGNMENT(LIST_ENTRY) == 2, "bad " "alignment");
+        static_assert(sizeof(UNKNOWN_TYPE) == 1, "unknown");
+        #ifdef _WIN64
+        C_ASSERT(sizeof(LIST_ENTRY) == 16);
+        #else
+        C_ASSERT(sizeof(LIST_ENTRY) == 8);
+        #endif
+        #if defined(__cplusplus)
+        C_ASSERT(FIELD_OFFSET(LIST_ENTRY, Flink) == 0);
+        #endif
+    "#);
+
+    let results = root.static_asserts.iter().map(|a| (a.expr.as_str(), a.results.iter().map(|(arch, r)| (arch.to_string(), *r)).collect::<Vec<_>>())).collect::<Vec<_>>();
+    let r = |results: &[(&str, bool)]| results.iter().map(|(arch, r)| (arch.to_string(), *r)).collect::<Vec<_>>();
+    assert_eq!(results, [
+        ("sizeof ( LIST_ENTRY ) == 16",                 r(&[("x86", false), ("x64", true),  ("arm64", true) ])),
+        ("FIELD_OFFSET ( LIST_ENTRY , Blink ) == 4",    r(&[("x86", true),  ("x64", false), ("arm64", false)])),
+        ("TYPE_ALIGNMENT ( LIST_ENTRY ) == 2",          r(&[("x86", false), ("x64", false), ("arm64", false)])),
+        ("sizeof ( UNKNOWN_TYPE ) == 1",                r(&[])),
+        ("sizeof ( LIST_ENTRY ) == 16",                 r(&[                ("x64", true),  ("arm64", true) ])),
+        ("sizeof ( LIST_ENTRY ) == 8",                  r(&[("x86", true)                                   ])),
+        ("FIELD_OFFSET ( LIST_ENTRY , Flink ) == 0",    r(&[("x86", true),  ("x64", true),  ("arm64", true) ])),
+    ]);
+    assert_eq!(root.static_asserts[2].message.as_deref(), Some("bad alignment"));
+    assert_eq!(root.static_asserts[5].condition.as_deref(), Some("!defined(_WIN64)"));
+    assert!(root.static_asserts[0].types.iter().map(|t| t.as_str()).eq(["LIST_ENTRY"]));
+    assert_eq!(root.eval_layout_expr("sizeof(LIST_ENTRY) == 16", Arch::X64), Some(1));
+    assert_eq!(root.eval_layout_expr("FIELD_OFFSET(LIST_ENTRY, Blink) + TYPE_ALIGNMENT(LIST_ENTRY)", Arch::X86), Some(8));
+    assert_eq!(root.eval_layout_expr("sizeof(UNKNOWN_TYPE)", Arch::X86), None);
+
+    let issues = root.structs.get("LIST_ENTRY").unwrap().issues.iter().map(|i| i.message.as_str()).collect::<Vec<_>>();
+    assert_eq!(issues, [
+        "`sizeof ( LIST_ENTRY ) == 16` does not hold for x86",
+        "`FIELD_OFFSET ( LIST_ENTRY , Blink ) == 4` does not hold for x64, arm64",
+        "`TYPE_ALIGNMENT ( LIST_ENTRY ) == 2` does not hold for x86, x64, arm64",
+    ]);
+}
+
+#[test] fn test_redefinitions() {
+    let mut root = Root::new();
+    root.add_from_cpp_src(Path::new("d3d9types.h").into(), r#"
+        typedef struct _D3DVECTOR { float x; float y; float z; } D3DVECTOR;
+        typedef struct _D3DRECT { LONG x1; LONG y1; LONG x2; LONG y2; } D3DRECT;
+        typedef enum _D3DFILLMODE { D3DFILL_POINT = 1, D3DFILL_WIREFRAME = 2, D3DFILL_SOLID = 3 } D3DFILLMODE;
+        typedef struct _D3DCAPS {
+        #if(DIRECT3D_VERSION >= 0x0900)
+            DWORD Reserved5;
+        #else
+            float Reserved5;
+        #endif
+        } D3DCAPS;
+        typedef enum _D3DSHADEMODE { D3DSHADE_FLAT = 1, D3DSHADE_GOURAUD = 2 } D3DSHADEMODE;
+    "#).unwrap();
+    root.add_from_cpp_src(Path::new("d3dtypes.h").into(), r#"
+        typedef struct _D3DVECTOR { float x; float y; float z; } D3DVECTOR;
+        typedef struct _D3DRECT { LONG x1; LONG y1; DWORD x2; } D3DRECT;
+        typedef enum _D3DFILLMODE { D3DFILL_POINT = 0x1, D3DFILL_WIREFRAME = 2, D3DFILL_SOLID = 4 } D3DFILLMODE;
+        typedef struct _D3DCAPS {
+        #if(DIRECT3D_VERSION >= 0x0900)
+            DWORD Reserved5;
+        #endif
+        } D3DCAPS;
+        typedef enum _D3DSHADEMODE {
+        #ifdef D3D_FLAT
+            D3DSHADE_FLAT = 1,
+        #endif
+            D3DSHADE_GOURAUD = 2,
+            D3DSHADE_GOURAUD = 3,
+        } D3DSHADEMODE;
+    "#).unwrap();
+    root.cleanup();
+
+    let vector = root.structs.get("D3DVECTOR").unwrap();
+    assert_eq!(vector.defined_at.len(), 2);
+    assert!(vector.issues.is_empty());
+
+    let rect = root.structs.get("D3DRECT").unwrap();
+    assert_eq!(rect.defined_at.len(), 2);
+    let issues = rect.issues.iter().map(|i| i.message.as_str()).collect::<Vec<_>>();
+    assert_eq!(issues, [
+        "`struct D3DRECT` redefined differently than at `d3d9types.h:3:25`: field 2 is `LONG x2` vs `DWORD x2`",
+        "`struct D3DRECT` redefined differently than at `d3d9types.h:3:25`: field `y2` missing from redefinition",
+    ]);
+
+    let fill = root.enums.get("D3DFILLMODE").unwrap();
+    let issues = fill.issues.iter().map(|i| i.message.as_str()).collect::<Vec<_>>();
+    assert_eq!(issues, ["`enum D3DFILLMODE` redefined differently than at `d3d9types.h:4:23`: `D3DFILL_SOLID` is `3` vs `4`"]);
+
+    let caps = root.structs.get("D3DCAPS").unwrap();
+    let issues = caps.issues.iter().map(|i| i.message.as_str()).collect::<Vec<_>>();
+    assert_eq!(issues, ["`struct D3DCAPS` redefined differently than at `d3d9types.h:5:25`: `Reserved5` variants are `float Reserved5 (#if !(DIRECT3D_VERSION >= 0x0900))` vs `(none)`"]);
+
+    let shade = root.enums.get("D3DSHADEMODE").unwrap();
+    let issues = shade.issues.iter().map(|i| i.message.as_str()).collect::<Vec<_>>();
+    assert_eq!(issues, [
+        "`enum D3DSHADEMODE` redefined differently than at `d3d9types.h:12:23`: `D3DSHADE_FLAT` is declared under `(no condition)` vs `defined(D3D_FLAT)`",
+        "duplicate enumerator `D3DSHADE_GOURAUD`",
+    ]);
+}
+
+#[test] fn test_flags() {
+    let root = Root::from_cpp_src(r#"
+        #define D3D12_SHADER_COMPONENT_MAPPING_SHIFT 3
+
+        typedef enum D3D12_RESOURCE_FLAGS {
+            D3D12_RESOURCE_FLAG_NONE                    = 0,
+            D3D12_RESOURCE_FLAG_ALLOW_RENDER_TARGET     = 0x1,
+            D3D12_RESOURCE_FLAG_ALLOW_DEPTH_STENCIL     = 0x2,
+            D3D12_RESOURCE_FLAG_BOTH                    = ( D3D12_RESOURCE_FLAG_ALLOW_RENDER_TARGET | D3D12_RESOURCE_FLAG_ALLOW_DEPTH_STENCIL ),
+        } D3D12_RESOURCE_FLAGS;
+        DEFINE_ENUM_FLAG_OPERATORS( D3D12_RESOURCE_FLAGS );
+        DEFINE_ENUM_FLAG_OPERATORS( D3D12_UNDEFINED_FLAGS );
+
+        typedef enum D3D12_SEQUENTIAL {
+            D3D12_SEQUENTIAL_A,
+            D3D12_SEQUENTIAL_B,
+            D3D12_SEQUENTIAL_C,
+            D3D12_SEQUENTIAL_D = D3D12_SEQUENTIAL_B + ( 1 << D3D12_SHADER_COMPONENT_MAPPING_SHIFT ),
+            D3D12_SEQUENTIAL_E,
+        } D3D12_SEQUENTIAL;
+
+        typedef enum _D3DGUESSED {
+            D3DGUESSED_A = 1 << 0,
+            D3DGUESSED_B = 1 << 1,
+            D3DGUESSED_C = 1 << 2,
+        } D3DGUESSED;
+    "#);
+
+    let sequential = root.enums.get("D3D12_SEQUENTIAL").unwrap().values.values_by_insert().map(|v| v.evaluated).collect::<Vec<_>>();
+    assert_eq!(sequential, [Some(0), Some(1), Some(2), Some(9), Some(10)]);
+    assert!(root.flags.get("D3D12_SEQUENTIAL").is_none());
+
+    let flags = root.flags.get("D3D12_RESOURCE_FLAGS").unwrap();
+    assert_eq!(flags.enum_.as_str(), "D3D12_RESOURCE_FLAGS");
+    assert_eq!(flags.operators_at.len(), 1);
+    assert_eq!(flags.bits.iter().map(|(id, v)| (id.as_str(), *v)).collect::<Vec<_>>(), [("D3D12_RESOURCE_FLAG_ALLOW_RENDER_TARGET", 1), ("D3D12_RESOURCE_FLAG_ALLOW_DEPTH_STENCIL", 2)]);
+    assert_eq!(flags.combinations.iter().map(|(id, v)| (id.as_str(), *v)).collect::<Vec<_>>(), [("D3D12_RESOURCE_FLAG_NONE", 0), ("D3D12_RESOURCE_FLAG_BOTH", 3)]);
+
+    assert!(root.flags.get("D3D12_UNDEFINED_FLAGS").is_none()); // warned about instead
+
+    let guessed = root.flags.get("D3DGUESSED").unwrap();
+    assert!(guessed.operators_at.is_empty());
+    assert_eq!(guessed.bits.len(), 3);
+}
+
+#[test] fn test_errors() {
+    let root = Root::from_cpp_src(r#"
+        #define SEVERITY_SUCCESS    0
+        #define SEVERITY_ERROR      1
+        #define FACILITY_WIN32      7
+        #define _HRESULT_TYPEDEF_(_sc) ((HRESULT)_sc)
+        #define MAKE_HRESULT(sev,fac,code) \
+            ((HRESULT) (((unsigned long)(sev)<<31) | ((unsigned long)(fac)<<16) | ((unsigned long)(code))) )
+
+        //
+        // MessageId: ERROR_FILE_NOT_FOUND
+        //
+        // MessageText:
+        //
+        // The system cannot find the file specified.
+        //
+        #define ERROR_FILE_NOT_FOUND             2L
+
+        //
+        // MessageId: E_UNEXPECTED
+        //
+        // MessageText:
+        //
+        // Catastrophic failure
+        //
+        #define E_UNEXPECTED                     _HRESULT_TYPEDEF_(0x8000FFFFL)
+
+        //
+        // MessageId: STATUS_GUARD_PAGE_VIOLATION
+        //
+        // MessageText:
+        //
+        // {EXCEPTION}
+        // Guard Page Exception
+        // A page of memory that marks the end of a data structure, such as a stack or an array, has been accessed.
+        //
+        #define STATUS_GUARD_PAGE_VIOLATION      ((NTSTATUS)0x80000001L)    // winnt
+
+        #define E_ACCESSDENIED_WIN32 MAKE_HRESULT(SEVERITY_ERROR, FACILITY_WIN32, 5) // not a real SDK define
+        #define NOT_AN_ERROR 42
+    "#);
+
+    let error_file_not_found = root.errors.get("ERROR_FILE_NOT_FOUND").unwrap();
+    assert_eq!(error_file_not_found.kind,       ErrorCodeKind::Win32);
+    assert_eq!(error_file_not_found.value,      2);
+    assert_eq!(error_file_not_found.severity(), ErrorSeverity::Error);
+    assert_eq!(error_file_not_found.message.as_deref(), Some("The system cannot find the file specified."));
+
+    let e_unexpected = root.errors.get("E_UNEXPECTED").unwrap();
+    assert_eq!(e_unexpected.kind,       ErrorCodeKind::HResult);
+    assert_eq!(e_unexpected.value,      0x8000FFFF);
+    assert_eq!(e_unexpected.facility(), Some(0));
+    assert_eq!(e_unexpected.message.as_deref(), Some("Catastrophic failure"));
+
+    let status_guard_page_violation = root.errors.get("STATUS_GUARD_PAGE_VIOLATION").unwrap();
+    assert_eq!(status_guard_page_violation.kind,        ErrorCodeKind::NtStatus);
+    assert_eq!(status_guard_page_violation.severity(),  ErrorSeverity::Warning);
+    assert_eq!(status_guard_page_violation.message.as_deref(), Some("{EXCEPTION}\nGuard Page Exception\nA page of memory that marks the end of a data structure, such as a stack or an array, has been accessed."));
+
+    let e_accessdenied = root.errors.get("E_ACCESSDENIED_WIN32").unwrap();
+    assert_eq!(e_accessdenied.value,        0x80070005);
+    assert_eq!(e_accessdenied.facility(),   Some(7));
+    assert_eq!(e_accessdenied.message,      None);
+
+    assert!(root.errors.get("NOT_AN_ERROR").is_none());
+    assert!(root.errors.get("SEVERITY_ERROR").is_none());
+    assert_eq!(root.constants.get("FACILITY_WIN32").unwrap().value.as_deref(), Some("7"));
+    assert_eq!(root.macros.get("MAKE_HRESULT").unwrap().params.len(), 3);
+}
+
+#[test] fn test_iids() {
+    let root = Root::from_cpp_src(r#"
+        EXTERN_C const IID IID_IUnknown;
+        MIDL_INTERFACE("00000000-0000-0000-C000-000000000046")
+        IUnknown
+        {
+        public:
+            virtual HRESULT STDMETHODCALLTYPE QueryInterface(REFIID riid, void **ppvObject) = 0;
+        };
+
+        interface DECLSPEC_UUID("8bcf1f58-9fe7-4583-8ac6-e2adc465c8bb") DECLSPEC_NOVTABLE IXAudio2;
+        DECLARE_INTERFACE_(IXAudio2, IUnknown)
+        {
+            STDMETHOD(QueryInterface) (THIS_ REFIID riid, void** ppvInterface) PURE;
+        };
+
+        DEFINE_GUID(IID_IDirect3D9, 0x81bdcbca, 0x64d4, 0x426d, 0xae, 0x8d, 0xad, 0x1, 0x47, 0xf4, 0x27, 0x5c);
+        DECLARE_INTERFACE_(IDirect3D9, IUnknown)
+        {
+            STDMETHOD(QueryInterface)(THIS_ REFIID riid, void** ppvObj) PURE;
+        };
+
+        struct __declspec(uuid("00000000-0000-0000-0000-000000000001")) __declspec(novtable) IMismatch;
+        DEFINE_GUID(IID_IMismatch, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x2);
+        DECLARE_INTERFACE(IMismatch)
+        {
+        };
+    "#);
+
+    let iunknown = root.interfaces.get("IUnknown").unwrap();
+    assert_eq!(iunknown.iid, Guid::parse("00000000-0000-0000-C000-000000000046"));
+    assert_eq!(iunknown.iid_constant.as_deref(), Some("IID_IUnknown"));
+
+    let ixaudio2 = root.interfaces.get("IXAudio2").unwrap();
+    assert_eq!(ixaudio2.iid, Guid::parse("8bcf1f58-9fe7-4583-8ac6-e2adc465c8bb"));
+    assert_eq!(ixaudio2.iid_constant, None);
+
+    let idirect3d9 = root.interfaces.get("IDirect3D9").unwrap();
+    assert_eq!(idirect3d9.iid, Guid::parse("81BDCBCA-64D4-426D-AE8D-AD0147F4275C"));
+    assert_eq!(idirect3d9.iid_constant.as_deref(), Some("IID_IDirect3D9"));
+
+    let imismatch = root.interfaces.get("IMismatch").unwrap();
+    assert_eq!(imismatch.iid, Guid::parse("00000000-0000-0000-0000-000000000001")); // first wins
+}
+
+#[test] fn test_declspecs() {
+    let root = Root::from_cpp_src(r#"
+        __declspec(dllimport) HRESULT WINAPI DllImported(void);
+        struct __declspec(uuid("00000000-0000-0000-0000-000000000001")) __declspec(novtable) IAfter;
+        DECLARE_INTERFACE(IAfter)
+        {
+        };
+        typedef struct DECLSPEC_ALIGN(16) ALIGNED { int a; } ALIGNED;
+        typedef struct DECLSPEC_ALIGN NOT_A_DECLSPEC { int b; } NOT_A_DECLSPEC; // e.g. an unexpanded macro
+        typedef struct AFTER { int c; } AFTER;
+    "#);
+
+    assert_eq!(root.interfaces.get("IAfter").unwrap().iid, Guid::parse("00000000-0000-0000-0000-000000000001"));
+    assert_eq!(root.structs.get("ALIGNED").unwrap().align, Some(16));
+    assert!(root.structs.get("AFTER").is_some());
+}
+
+#[test] fn test_interface_forms() {
+    let root = Root::from_cpp_src(r#"
+        DECLARE_INTERFACE(IBase) {
+            STDMETHOD(QueryInterface)(THIS_ REFIID riid, void** ppvObj) PURE;
+        };
+
+        DECLARE_INTERFACE_(ISameLine, IBase) { // trailing comment
+            STDMETHOD(SameLine)(THIS) PURE;
+        };  // ISameLine
+
+        DECLARE_INTERFACE_IID_(IWithIid, IBase, "00000000-0000-0000-0000-000000000001")
+        {
+            STDMETHOD_(ULONG, WithIid)(THIS) PURE;
+        };
+
+        MIDL_INTERFACE("00000000-0000-0000-0000-000000000002") /* comment */
+        IMidl : public IBase
+        {
+        public:
+            virtual HRESULT STDMETHODCALLTYPE Midl( void) = 0;
+        };
+
+        struct __declspec(uuid("00000000-0000-0000-0000-000000000003")) __declspec(novtable) IDeclspec : public IBase
+        {
+            virtual HRESULT STDMETHODCALLTYPE Declspec( void) = 0;
+        };
+
+        interface DECLSPEC_UUID("00000000-0000-0000-0000-000000000004") DECLSPEC_NOVTABLE IDeclspecUuid : public IBase {
+            STDMETHOD(DeclspecUuid)(THIS) PURE;
+        };
+
+        interface DX_DECLARE_INTERFACE("00000000-0000-0000-0000-000000000005") IDxDeclare : public IBase
+        {
+            STDMETHOD_(void, DxDeclare)(
+                UINT count
+                ) PURE;
+        }; // interface IDxDeclare
+
+        interface DWRITE_DECLARE_INTERFACE("00000000-0000-0000-0000-000000000006") IDWriteDeclare : public IBase
+        {
+            STDMETHOD(DWriteDeclare)() PURE;
+        };
+
+        DECLARE_INTERFACE(IOneLine) { STDMETHOD(QueryInterface)(THIS_ REFIID riid, void** ppvObj) PURE; STDMETHOD_(ULONG,AddRef)(THIS) PURE; STDMETHOD_(ULONG,Release)(THIS) PURE; };
+
+        interface ICStyle
+        {
+            CONST_VTBL struct ICStyleVtbl *lpVtbl;
+        };
+    "#);
+
+    let summary = root.interfaces.values_by_insert().map(|i| (
+        i.id.as_str(),
+        i.base.as_ref().map(|b| b.as_str()),
+        i.iid.map(|iid| iid.to_string()),
+        i.methods().map(|m| m.f.id.as_str()).collect::<Vec<_>>(),
+    )).collect::<Vec<_>>();
+    let iid = |n: u32| Some(format!("00000000-0000-0000-0000-00000000000{}", n));
+    assert_eq!(summary, [
+        ("IBase",           None,           None,   vec!["QueryInterface"]),
+        ("ISameLine",       Some("IBase"),  None,   vec!["SameLine"]),
+        ("IWithIid",        Some("IBase"),  iid(1), vec!["WithIid"]),
+        ("IMidl",           Some("IBase"),  iid(2), vec!["Midl"]),
+        ("IDeclspec",       Some("IBase"),  iid(3), vec!["Declspec"]),
+        ("IDeclspecUuid",   Some("IBase"),  iid(4), vec!["DeclspecUuid"]),
+        ("IDxDeclare",      Some("IBase"),  iid(5), vec!["DxDeclare"]),
+        ("IDWriteDeclare",  Some("IBase"),  iid(6), vec!["DWriteDeclare"]),
+        ("IOneLine",        None,           None,   vec!["AddRef", "QueryInterface", "Release"]),
+    ]);
+}